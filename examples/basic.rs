@@ -1,6 +1,6 @@
 extern crate doryen_rs;
 
-use doryen_rs::{App, AppOptions, DoryenApi, Engine, ScanCode, TextAlign, UpdateEvent};
+use doryen_rs::{App, AppOptions, DoryenApi, Engine, FontLayout, ScanCode, TextAlign, UpdateEvent};
 
 // this part makes it possible to compile to wasm32 target
 #[cfg(target_arch = "wasm32")]
@@ -131,12 +131,14 @@ fn main() {
         screen_height: CONSOLE_HEIGHT * 8,
         window_title: "my roguelike".to_owned(),
         font_path: "terminal_8x8.png".to_owned(),
+        font_layout: FontLayout::Cp437,
         vsync: true,
         fullscreen: false,
         show_cursor: true,
         resizable: true,
         intercept_close_request: false,
         max_fps: 0,
+        hot_reload: false,
     });
     app.set_engine(Box::new(MyRoguelike::new()));
     app.run();
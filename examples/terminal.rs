@@ -0,0 +1,72 @@
+extern crate doryen_rs;
+
+use doryen_rs::{AppOptions, DoryenApi, Engine, ScanCode, TextAlign, UpdateEvent};
+
+/*
+* This example runs the game in the current terminal instead of an OpenGL window.
+* Move the @ with the arrows, press ESC to exit.
+*/
+
+struct MyRoguelike {
+    player_pos: (i32, i32),
+}
+
+impl Engine for MyRoguelike {
+    fn update(&mut self, api: &mut dyn DoryenApi) -> Option<UpdateEvent> {
+        let input = api.input();
+        if input.key(ScanCode::Escape) {
+            return Some(UpdateEvent::Exit);
+        }
+        if input.key(ScanCode::Left) {
+            self.player_pos.0 = (self.player_pos.0 - 1).max(1);
+        } else if input.key(ScanCode::Right) {
+            self.player_pos.0 = (self.player_pos.0 + 1).min(38);
+        }
+        if input.key(ScanCode::Up) {
+            self.player_pos.1 = (self.player_pos.1 - 1).max(1);
+        } else if input.key(ScanCode::Down) {
+            self.player_pos.1 = (self.player_pos.1 + 1).min(18);
+        }
+        None
+    }
+    fn render(&mut self, api: &mut dyn DoryenApi) {
+        let con = api.con();
+        con.rectangle(
+            0,
+            0,
+            40,
+            20,
+            Some((128, 128, 128, 255)),
+            Some((0, 0, 0, 255)),
+            Some('.' as u16),
+        );
+        con.print(
+            20,
+            19,
+            "arrows to move, ESC to exit",
+            TextAlign::Center,
+            Some((255, 255, 255, 255)),
+            None,
+        );
+        con.ascii(self.player_pos.0, self.player_pos.1, '@' as u16);
+        con.fore(self.player_pos.0, self.player_pos.1, (255, 255, 0, 255));
+    }
+}
+
+#[cfg(unix)]
+fn main() {
+    let mut app = doryen_rs::TerminalApp::new_stdout(AppOptions {
+        console_width: 40,
+        console_height: 20,
+        ..Default::default()
+    });
+    app.set_engine(Box::new(MyRoguelike {
+        player_pos: (20, 10),
+    }));
+    app.run().unwrap();
+}
+
+#[cfg(not(unix))]
+fn main() {
+    println!("The terminal backend is only available on unix platforms");
+}
@@ -0,0 +1,383 @@
+use std::fmt::Write;
+
+use unicode_width::UnicodeWidthChar;
+
+use crate::color::Color;
+use crate::console::Console;
+use crate::cp437::cp437_to_char;
+
+/// the 16 colors of the VGA palette, in ANSI order
+const ANSI_PALETTE: [Color; 16] = [
+    (0, 0, 0, 255),
+    (170, 0, 0, 255),
+    (0, 170, 0, 255),
+    (170, 85, 0, 255),
+    (0, 0, 170, 255),
+    (170, 0, 170, 255),
+    (0, 170, 170, 255),
+    (170, 170, 170, 255),
+    (85, 85, 85, 255),
+    (255, 85, 85, 255),
+    (85, 255, 85, 255),
+    (255, 255, 85, 255),
+    (85, 85, 255, 255),
+    (255, 85, 255, 255),
+    (85, 255, 255, 255),
+    (255, 255, 255, 255),
+];
+const DEFAULT_FORE: usize = 7;
+const DEFAULT_BACK: usize = 0;
+const DEFAULT_WIDTH: u32 = 80;
+const SAUCE_SIZE: usize = 128;
+/// characters below this line are ignored, so that a hostile file can't allocate a huge console
+const MAX_HEIGHT: u32 = 10_000;
+/// larger widths are reduced to this value, for the same reason
+const MAX_WIDTH: u32 = 1_000;
+
+impl Console {
+    /// Create a console from the content of an ANSI art file (.ans).
+    ///
+    /// Characters are CP437 codes and are stored as is in the console, so you need a font with a CP437 layout.
+    /// SGR color codes (16 colors, 256 colors and 24-bit), cursor movements and the SAUCE record are supported.
+    /// The console width is read from the SAUCE record if there's one, else it's `width` or 80 by default,
+    /// up to 1000 columns.
+    /// The console height is the number of lines in the file, up to 10000 lines.
+    ///
+    /// Example
+    /// ```
+    /// use doryen_rs::Console;
+    /// let con = Console::from_ansi(b"\x1b[1;31mA\x1b[0;44mB\r\nC", None);
+    /// assert_eq!(con.get_size(), (80, 2));
+    /// assert_eq!(con.get_ascii(0, 0), Some('A' as u16));
+    /// assert_eq!(con.get_fore(0, 0), Some((255, 85, 85, 255)));
+    /// assert_eq!(con.get_back(1, 0), Some((0, 0, 170, 255)));
+    /// assert_eq!(con.get_ascii(0, 1), Some('C' as u16));
+    /// ```
+    pub fn from_ansi(buf: &[u8], width: Option<u32>) -> Console {
+        let (content, sauce_width) = strip_sauce(buf);
+        let width = sauce_width
+            .or(width)
+            .unwrap_or(DEFAULT_WIDTH)
+            .clamp(1, MAX_WIDTH);
+        let mut parser = AnsiParser::new(width);
+        parser.parse(content);
+        let height = parser.rows.len().max(1) as u32;
+        let mut con = Console::new(width, height);
+        con.clear(
+            Some(ANSI_PALETTE[DEFAULT_FORE]),
+            Some(ANSI_PALETTE[DEFAULT_BACK]),
+            Some(' ' as u16),
+        );
+        let pot_width = con.get_pot_width() as usize;
+        for (y, row) in parser.rows.iter().enumerate() {
+            for (x, &(ascii, fore, back)) in row.iter().enumerate() {
+                let off = x + y * pot_width;
+                con.borrow_mut_ascii()[off] = ascii;
+                con.borrow_mut_foreground()[off] = fore;
+                con.borrow_mut_background()[off] = back;
+            }
+        }
+        con
+    }
+
+    /// Dump a region of the console to a string using 24-bit ANSI escape sequences.
+    /// The region is clipped to the console size. Glyphs below 256 are considered CP437 codes
+    /// and converted to unicode, other glyphs are considered unicode code points.
+    /// Each line ends with a color reset and a line feed.
+    ///
+    /// Example
+    /// ```
+    /// use doryen_rs::{Console, TextAlign};
+    /// let mut con = Console::new(10, 2);
+    /// con.print(0, 0, "hi", TextAlign::Left, Some((255, 0, 0, 255)), Some((0, 0, 0, 255)));
+    /// assert_eq!(
+    ///     con.to_ansi(0, 0, 2, 1),
+    ///     "\x1b[38;2;255;0;0m\x1b[48;2;0;0;0mhi\x1b[0m\n"
+    /// );
+    /// ```
+    pub fn to_ansi(&self, x: i32, y: i32, w: u32, h: u32) -> String {
+        let mut out = String::new();
+        let minx = x.max(0);
+        let miny = y.max(0);
+        let maxx = x.saturating_add(w as i32).min(self.get_width() as i32);
+        let maxy = y.saturating_add(h as i32).min(self.get_height() as i32);
+        for cy in miny..maxy {
+            let mut fore = None;
+            let mut back = None;
+            let mut wide = false;
+            for cx in minx..maxx {
+                if wide {
+                    // second half of a wide character
+                    wide = false;
+                    continue;
+                }
+                let cell_fore = self.unsafe_get_fore(cx, cy);
+                let cell_back = self.unsafe_get_back(cx, cy);
+                if fore != Some(cell_fore) {
+                    write_sgr_color(&mut out, 38, cell_fore);
+                    fore = Some(cell_fore);
+                }
+                if back != Some(cell_back) {
+                    write_sgr_color(&mut out, 48, cell_back);
+                    back = Some(cell_back);
+                }
+                let ascii =
+                    self.borrow_ascii()[cx as usize + cy as usize * self.get_pot_width() as usize];
+                let ch = glyph_to_char(ascii);
+                wide = is_wide_glyph(ascii);
+                out.push(ch);
+            }
+            out.push_str("\x1b[0m\n");
+        }
+        out
+    }
+}
+
+fn write_sgr_color(out: &mut String, sgr: u8, color: Color) {
+    write!(out, "\x1b[{};2;{};{};{}m", sgr, color.0, color.1, color.2).ok();
+}
+
+/// whether a glyph is displayed as an East Asian wide character, using two cells
+pub(crate) fn is_wide_glyph(ascii: u32) -> bool {
+    glyph_to_char(ascii).width() == Some(2)
+}
+
+pub(crate) fn glyph_to_char(ascii: u32) -> char {
+    if ascii < 256 {
+        cp437_to_char(ascii as u8)
+    } else {
+        char::from_u32(ascii).unwrap_or('?')
+    }
+}
+
+/// remove the SAUCE record and the EOF character. Returns the content and the width from the SAUCE record
+fn strip_sauce(buf: &[u8]) -> (&[u8], Option<u32>) {
+    let mut content = buf;
+    let mut width = None;
+    if buf.len() >= SAUCE_SIZE && buf[buf.len() - SAUCE_SIZE..].starts_with(b"SAUCE00") {
+        let sauce = &buf[buf.len() - SAUCE_SIZE..];
+        // data type 1 = character, tinfo1 = number of columns
+        if sauce[94] == 1 {
+            let tinfo1 = u16::from_le_bytes([sauce[96], sauce[97]]);
+            if tinfo1 > 0 {
+                width = Some(u32::from(tinfo1));
+            }
+        }
+        content = &buf[..buf.len() - SAUCE_SIZE];
+    }
+    if let Some(eof) = content.iter().position(|&b| b == 0x1a) {
+        content = &content[..eof];
+    }
+    (content, width)
+}
+
+#[derive(Clone, Copy)]
+enum AnsiColor {
+    Palette(usize),
+    Rgb(Color),
+}
+
+struct AnsiParser {
+    width: u32,
+    rows: Vec<Vec<(u32, Color, Color)>>,
+    x: u32,
+    y: u32,
+    saved: (u32, u32),
+    fore: AnsiColor,
+    back: AnsiColor,
+    bold: bool,
+    blink: bool,
+    reverse: bool,
+}
+
+impl AnsiParser {
+    fn new(width: u32) -> Self {
+        Self {
+            width,
+            rows: Vec::new(),
+            x: 0,
+            y: 0,
+            saved: (0, 0),
+            fore: AnsiColor::Palette(DEFAULT_FORE),
+            back: AnsiColor::Palette(DEFAULT_BACK),
+            bold: false,
+            blink: false,
+            reverse: false,
+        }
+    }
+    fn parse(&mut self, buf: &[u8]) {
+        let mut i = 0;
+        while i < buf.len() {
+            let b = buf[i];
+            i += 1;
+            match b {
+                0x1b if buf.get(i) == Some(&b'[') => {
+                    let start = i + 1;
+                    let mut end = start;
+                    while end < buf.len() && !(0x40..=0x7e).contains(&buf[end]) {
+                        end += 1;
+                    }
+                    if end >= buf.len() {
+                        return;
+                    }
+                    let params = String::from_utf8_lossy(&buf[start..end]).into_owned();
+                    self.csi(&params, buf[end]);
+                    i = end + 1;
+                }
+                b'\r' => self.x = 0,
+                b'\n' => {
+                    self.x = 0;
+                    self.set_row(self.y.saturating_add(1));
+                }
+                b'\t' => self.x = ((self.x / 8 + 1) * 8).min(self.width - 1),
+                _ => self.put(b),
+            }
+        }
+    }
+    fn put(&mut self, ascii: u8) {
+        if self.x >= self.width {
+            self.x = 0;
+            self.set_row(self.y.saturating_add(1));
+        }
+        if self.y >= MAX_HEIGHT {
+            return;
+        }
+        let (mut fore, mut back) = (self.fore_color(), self.back_color());
+        if self.reverse {
+            std::mem::swap(&mut fore, &mut back);
+        }
+        let default_cell = (
+            ' ' as u32,
+            ANSI_PALETTE[DEFAULT_FORE],
+            ANSI_PALETTE[DEFAULT_BACK],
+        );
+        while self.rows.len() <= self.y as usize {
+            self.rows.push(Vec::new());
+        }
+        let row = &mut self.rows[self.y as usize];
+        while row.len() <= self.x as usize {
+            row.push(default_cell);
+        }
+        row[self.x as usize] = (u32::from(ascii), fore, back);
+        self.x += 1;
+    }
+    /// move the cursor to a row, stopping just below the last allowed row
+    fn set_row(&mut self, y: u32) {
+        self.y = y.min(MAX_HEIGHT);
+    }
+    fn fore_color(&self) -> Color {
+        match self.fore {
+            AnsiColor::Palette(idx) if idx < 8 && self.bold => ANSI_PALETTE[idx + 8],
+            AnsiColor::Palette(idx) => ANSI_PALETTE[idx],
+            AnsiColor::Rgb(col) => col,
+        }
+    }
+    fn back_color(&self) -> Color {
+        // ICE colors : blink means bright background
+        match self.back {
+            AnsiColor::Palette(idx) if idx < 8 && self.blink => ANSI_PALETTE[idx + 8],
+            AnsiColor::Palette(idx) => ANSI_PALETTE[idx],
+            AnsiColor::Rgb(col) => col,
+        }
+    }
+    fn csi(&mut self, params: &str, command: u8) {
+        let private = params.starts_with('?');
+        let values: Vec<u32> = params
+            .trim_start_matches('?')
+            .split(';')
+            .map(|p| p.parse().unwrap_or(0))
+            .collect();
+        let count = values.first().copied().unwrap_or(0).max(1);
+        match command {
+            _ if private => (),
+            b'm' => self.sgr(&values),
+            b'A' => self.y = self.y.saturating_sub(count),
+            b'B' => self.set_row(self.y.saturating_add(count)),
+            b'C' => self.x = self.x.saturating_add(count).min(self.width - 1),
+            b'D' => self.x = self.x.saturating_sub(count),
+            b'H' | b'f' => {
+                self.set_row(values.first().copied().unwrap_or(1).max(1) - 1);
+                self.x = (values.get(1).copied().unwrap_or(1).max(1) - 1).min(self.width - 1);
+            }
+            b's' => self.saved = (self.x, self.y),
+            b'u' => {
+                self.x = self.saved.0;
+                self.y = self.saved.1;
+            }
+            b'J' if values[0] == 2 => {
+                self.rows.clear();
+                self.x = 0;
+                self.y = 0;
+            }
+            b'K' => {
+                if let Some(row) = self.rows.get_mut(self.y as usize) {
+                    row.truncate(self.x as usize);
+                }
+            }
+            _ => (),
+        }
+    }
+    fn sgr(&mut self, values: &[u32]) {
+        let mut i = 0;
+        while i < values.len() {
+            match values[i] {
+                0 => {
+                    self.fore = AnsiColor::Palette(DEFAULT_FORE);
+                    self.back = AnsiColor::Palette(DEFAULT_BACK);
+                    self.bold = false;
+                    self.blink = false;
+                    self.reverse = false;
+                }
+                1 => self.bold = true,
+                5 => self.blink = true,
+                7 => self.reverse = true,
+                22 => self.bold = false,
+                25 => self.blink = false,
+                27 => self.reverse = false,
+                v @ 30..=37 => self.fore = AnsiColor::Palette((v - 30) as usize),
+                39 => self.fore = AnsiColor::Palette(DEFAULT_FORE),
+                v @ 40..=47 => self.back = AnsiColor::Palette((v - 40) as usize),
+                49 => self.back = AnsiColor::Palette(DEFAULT_BACK),
+                v @ 90..=97 => self.fore = AnsiColor::Palette((v - 90 + 8) as usize),
+                v @ 100..=107 => self.back = AnsiColor::Palette((v - 100 + 8) as usize),
+                v @ (38 | 48) => {
+                    let (color, used) = extended_color(&values[i + 1..]);
+                    if let Some(color) = color {
+                        if v == 38 {
+                            self.fore = color;
+                        } else {
+                            self.back = color;
+                        }
+                    }
+                    i += used;
+                }
+                _ => (),
+            }
+            i += 1;
+        }
+    }
+}
+
+/// parse a 38/48 extended color (5;n or 2;r;g;b). Returns the color and the number of parameters used
+fn extended_color(values: &[u32]) -> (Option<AnsiColor>, usize) {
+    match values {
+        [5, n, ..] => (Some(xterm_color(*n as u8)), 2),
+        [2, r, g, b, ..] => (Some(AnsiColor::Rgb((*r as u8, *g as u8, *b as u8, 255))), 4),
+        _ => (None, values.len()),
+    }
+}
+
+fn xterm_color(n: u8) -> AnsiColor {
+    match n {
+        0..=15 => AnsiColor::Palette(n as usize),
+        16..=231 => {
+            let n = n - 16;
+            let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            AnsiColor::Rgb((level(n / 36), level((n / 6) % 6), level(n % 6), 255))
+        }
+        _ => {
+            let grey = 8 + (n - 232) * 10;
+            AnsiColor::Rgb((grey, grey, grey, 255))
+        }
+    }
+}
@@ -1,10 +1,18 @@
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
 
 use crate::console::Console;
+use crate::error::LoadError;
 use crate::font::FontLoader;
+use crate::glyph::FontLayout;
 use crate::input::{DoryenInput, InputApi};
+use crate::layer::LayerStack;
 use crate::program::{set_texture_params, Program};
+use crate::renderer::{encode_png, SoftwareRenderer};
+#[cfg(feature = "ttf")]
+use crate::ttf::TtfFont;
+use crate::watch::Watcher;
 
 // shaders
 const DORYEN_VS: &str = include_str!("doryen_vs.glsl");
@@ -13,7 +21,7 @@ const DORYEN_FS: &str = include_str!("doryen_fs.glsl");
 // fps
 const MAX_FRAMESKIP: i32 = 5;
 const TICKS_PER_SECOND: f64 = 60.0;
-const SKIP_TICKS: f64 = 1.0 / TICKS_PER_SECOND;
+pub(crate) const SKIP_TICKS: f64 = 1.0 / TICKS_PER_SECOND;
 
 // default options
 pub const DEFAULT_CONSOLE_WIDTH: u32 = 80;
@@ -23,6 +31,14 @@ pub const DEFAULT_CONSOLE_HEIGHT: u32 = 45;
 pub trait DoryenApi {
     /// return the root console that you can use to draw things on the screen
     fn con(&mut self) -> &mut Console;
+    /// return the layers drawn over the root console when it is displayed.
+    /// Example
+    /// ```compile_fail
+    /// let ui = api.layers().push("ui", Layer::new(80, 45));
+    /// ui.opacity = 0.8;
+    /// ui.console.print(1, 1, "Hello", TextAlign::Left, None, None);
+    /// ```
+    fn layers(&mut self) -> &mut LayerStack;
     /// return the input API to check user mouse and keyboard input
     fn input(&mut self) -> &mut dyn InputApi;
     /// return the current framerate
@@ -53,23 +69,105 @@ pub trait DoryenApi {
     /// * greyscale : black pixels are transparent. Grey pixels are replaced by white semi-transparent pixels. Colored pixels are opaque. The font cannot have pure grey colors.
     /// * RGB : The top-left pixel's color is transparent. The font cannot have semi-transparent pixels but it can have pure grey pixels.
     fn set_font_path(&mut self, font_path: &str);
+    /// define how the glyphs are organized in the font. This changes how the characters printed on the root
+    /// console are converted into glyph indices, and the default character size of the next font loaded.
+    /// Example
+    /// ```compile_fail
+    /// api.set_font_layout(FontLayout::Tcod);
+    /// api.set_font_path("arial10x10.png");
+    /// ```
+    /// To print text with the same layout on an offscreen console, use its glyph map :
+    /// ```compile_fail
+    /// offscreen.set_glyph_map(api.con().glyph_map().clone());
+    /// ```
+    fn set_font_layout(&mut self, layout: FontLayout);
+    /// load an additional font and return its id, to be used in [`Layer::font`](crate::Layer::font).
+    /// The main font has the id 0. The character size is computed like for [`DoryenApi::set_font_path`]
+    /// and can be different from the main font's.
+    /// A layer using another font is drawn over the root console with its own cell size,
+    /// aligned in pixels. Use [`Layer::pixel_offset`](crate::Layer::pixel_offset) to fine-tune its position.
+    /// Example
+    /// ```compile_fail
+    /// let text_font = api.add_font("fonts/text_8x16.png", FontLayout::Cp437);
+    /// let mut layer = Layer::new(40, 20);
+    /// layer.font = text_font;
+    /// layer.console.set_glyph_map(FontLayout::Cp437.glyph_map());
+    /// layer.console.print(1, 1, "Hello", TextAlign::Left, None, None);
+    /// api.layers().push("text", layer);
+    /// ```
+    /// The terminal backend ignores the layers using another font.
+    /// TrueType/OpenType fonts are only supported as main font.
+    fn add_font(&mut self, font_path: &str, layout: FontLayout) -> usize;
+    /// return the error that happened while loading a font, if any. `font` is the id returned by
+    /// [`DoryenApi::add_font`], or 0 for the main font requested with [`DoryenApi::set_font_path`].
+    /// The error is cleared when the font is requested or reloaded again.
+    /// If the main font cannot be loaded, the previous one is kept. If there is no previous font,
+    /// the engine runs anyway but the consoles are rendered without glyphs.
+    /// Example
+    /// ```compile_fail
+    /// if let Some(err) = api.font_error(0) {
+    ///     eprintln!("{}", err);
+    ///     api.set_font_path("terminal_8x8.png");
+    /// }
+    /// ```
+    fn font_error(&self, font: usize) -> Option<&LoadError>;
+    /// watch a data file when [`AppOptions::hot_reload`] is enabled : [`Engine::file_changed`] is called when it's modified.
+    /// Fonts, [`crate::Image`] and [`crate::XpFile`] files are watched automatically.
+    fn watch_file(&mut self, path: &str);
     /// return the current screen size
     fn get_screen_size(&self) -> (u32, u32);
+    /// render the root console at the font's native resolution and return it as PNG file content.
+    /// This doesn't need a GL context and works on every platform, so you can for example offer
+    /// the screenshot as a download in a web page.
+    /// Returns None if the font has not been loaded yet.
+    fn capture_screen(&mut self) -> Option<Vec<u8>>;
 }
 
-struct DoryenApiImpl {
-    con: Console,
-    input: DoryenInput,
-    fps: u32,
-    average_fps: u32,
-    font_path: Option<String>,
-    screen_size: (u32, u32),
+pub(crate) struct DoryenApiImpl {
+    pub(crate) con: Console,
+    pub(crate) input: DoryenInput,
+    pub(crate) fps: u32,
+    pub(crate) average_fps: u32,
+    pub(crate) font_path: Option<String>,
+    pub(crate) font_layout: FontLayout,
+    /// errors of the fonts that could not be loaded, by font id (0 = main font)
+    pub(crate) font_errors: HashMap<usize, LoadError>,
+    /// path of the main font, reloaded when it changes
+    current_font: Option<String>,
+    /// path and layout of the fonts added with [`DoryenApi::add_font`]
+    font_requests: HashMap<usize, (String, FontLayout)>,
+    /// files watched for hot reloading, None if [`AppOptions::hot_reload`] is disabled
+    watcher: Option<Watcher>,
+    pub(crate) screen_size: (u32, u32),
+    pub(crate) renderer: Option<SoftwareRenderer>,
+    pub(crate) layers: LayerStack,
+    /// fonts added with [`DoryenApi::add_font`] that the backend has not started loading yet
+    pub(crate) new_fonts: Vec<(usize, String, FontLayout)>,
+    /// software renderers of the additional fonts, used by [`DoryenApi::capture_screen`]
+    pub(crate) font_renderers: HashMap<usize, SoftwareRenderer>,
+    font_count: usize,
+    /// root console with the layers composited over it
+    frame: Console,
+    /// areas covered by the composited layers in the last frame, None if there was no layer
+    layers_areas: Option<Vec<(i32, i32, u32, u32)>>,
+    /// main font when it's a TrueType/OpenType font
+    #[cfg(feature = "ttf")]
+    pub(crate) ttf: Option<TtfFont>,
+    /// frame with the code points replaced by the TrueType font atlas indices
+    #[cfg(feature = "ttf")]
+    ttf_frame: Console,
+    /// whether the TrueType font atlas has changed since the GL texture was uploaded
+    #[cfg(feature = "ttf")]
+    pub(crate) ttf_updated: bool,
 }
 
 impl DoryenApi for DoryenApiImpl {
     fn con(&mut self) -> &mut Console {
         &mut self.con
     }
+    fn layers(&mut self) -> &mut LayerStack {
+        &mut self.layers
+    }
     fn input(&mut self) -> &mut dyn InputApi {
         &mut self.input
     }
@@ -81,24 +179,241 @@ impl DoryenApi for DoryenApiImpl {
     }
     fn set_font_path(&mut self, font_path: &str) {
         self.font_path = Some(font_path.to_owned());
+        self.font_errors.remove(&0);
+        if let Some(watcher) = self.watcher.as_mut() {
+            if let Some(previous) = self.current_font.as_deref() {
+                watcher.unwatch(previous);
+            }
+            watcher.watch(font_path);
+        }
+        self.current_font = Some(font_path.to_owned());
+    }
+    fn set_font_layout(&mut self, layout: FontLayout) {
+        self.con.set_glyph_map(layout.glyph_map());
+        self.font_layout = layout;
+    }
+    fn add_font(&mut self, font_path: &str, layout: FontLayout) -> usize {
+        self.font_count += 1;
+        if let Some(watcher) = self.watcher.as_mut() {
+            watcher.watch(font_path);
+        }
+        self.font_requests
+            .insert(self.font_count, (font_path.to_owned(), layout.clone()));
+        self.new_fonts
+            .push((self.font_count, font_path.to_owned(), layout));
+        self.font_count
+    }
+    fn font_error(&self, font: usize) -> Option<&LoadError> {
+        self.font_errors.get(&font)
+    }
+    fn watch_file(&mut self, path: &str) {
+        if let Some(watcher) = self.watcher.as_mut() {
+            watcher.watch(path);
+        }
     }
 
     fn get_screen_size(&self) -> (u32, u32) {
         self.screen_size
     }
+
+    fn capture_screen(&mut self) -> Option<Vec<u8>> {
+        self.compose_frame();
+        let renderer = self.renderer.as_ref()?;
+        let mut img = renderer.render(self.frame());
+        let (char_width, char_height) = renderer.char_size();
+        let mut pass_con = Console::new(1, 1);
+        for layer in self.layers.passes() {
+            let layer_renderer = match layer.font {
+                0 => renderer,
+                font => match self.font_renderers.get(&font) {
+                    Some(layer_renderer) => layer_renderer,
+                    None => continue,
+                },
+            };
+            layer.prepare_pass(&mut pass_con);
+            #[cfg(feature = "ttf")]
+            if let (0, Some(ttf)) = (layer.font, self.ttf.as_mut()) {
+                ttf.remap(&mut pass_con);
+            }
+            let (x, y) = layer.pixel_position(char_width, char_height);
+            layer_renderer.render_over(&pass_con, &mut img, x, y);
+        }
+        Some(encode_png(&img))
+    }
 }
 
 impl DoryenApiImpl {
+    pub(crate) fn new(options: &AppOptions, input: DoryenInput) -> Self {
+        let mut con = Console::new(options.console_width, options.console_height);
+        con.set_glyph_map(options.font_layout.glyph_map());
+        Self {
+            input,
+            con,
+            fps: 0,
+            average_fps: 0,
+            font_path: None,
+            font_layout: options.font_layout.clone(),
+            font_errors: HashMap::new(),
+            current_font: None,
+            font_requests: HashMap::new(),
+            watcher: options.hot_reload.then(Watcher::new),
+            screen_size: (options.screen_width, options.screen_height),
+            renderer: None,
+            layers: LayerStack::new(),
+            new_fonts: Vec::new(),
+            font_renderers: HashMap::new(),
+            font_count: 0,
+            frame: Console::new(1, 1),
+            layers_areas: None,
+            #[cfg(feature = "ttf")]
+            ttf: None,
+            #[cfg(feature = "ttf")]
+            ttf_frame: Console::new(1, 1),
+            #[cfg(feature = "ttf")]
+            ttf_updated: false,
+        }
+    }
+    /// draw the layers over a copy of the root console
+    /// The cells covered by the layers in the previous frame are marked as modified
+    /// since the root console may be different below them.
+    pub(crate) fn compose_frame(&mut self) {
+        if self.layers.is_empty() {
+            for (x, y, w, h) in self.layers_areas.take().unwrap_or_default() {
+                self.con.mark_dirty(x, y, w, h);
+            }
+        } else {
+            self.layers.composite(&self.con, &mut self.frame);
+            let areas = self.layers.composited_areas();
+            for (x, y, w, h) in self.layers_areas.replace(areas).unwrap_or_default() {
+                self.frame.mark_dirty(x, y, w, h);
+            }
+        }
+        #[cfg(feature = "ttf")]
+        self.remap_ttf_frame();
+    }
+    /// convert the frame code points into TrueType font atlas indices,
+    /// rasterizing the new glyphs (including those of the layers rendered in their own pass)
+    #[cfg(feature = "ttf")]
+    fn remap_ttf_frame(&mut self) {
+        let ttf = match self.ttf.as_mut() {
+            Some(ttf) => ttf,
+            None => return,
+        };
+        let frame = if self.layers.is_empty() {
+            &self.con
+        } else {
+            &self.frame
+        };
+        self.ttf_frame.copy_from(frame);
+        ttf.remap(&mut self.ttf_frame);
+        for layer in self.layers.passes().filter(|layer| layer.font == 0) {
+            ttf.cache(&layer.console);
+        }
+        if ttf.take_dirty() {
+            let (char_width, char_height) = ttf.char_size();
+            self.renderer = Some(SoftwareRenderer::new(
+                ttf.atlas().clone(),
+                char_width,
+                char_height,
+            ));
+            self.ttf_updated = true;
+        }
+    }
+    /// the console to display, as computed by the last call to [`DoryenApiImpl::compose_frame`]
+    pub(crate) fn frame(&self) -> &Console {
+        #[cfg(feature = "ttf")]
+        if self.ttf.is_some() {
+            return &self.ttf_frame;
+        }
+        if self.layers.is_empty() {
+            &self.con
+        } else {
+            &self.frame
+        }
+    }
+    /// forget the modified areas once the frame has been uploaded
+    pub(crate) fn clear_dirty(&mut self) {
+        self.con.clear_dirty();
+        self.frame.clear_dirty();
+        #[cfg(feature = "ttf")]
+        self.ttf_frame.clear_dirty();
+    }
     pub fn clear_font_path(&mut self) {
         self.font_path = None;
     }
+    /// save a screenshot of the root console in a file. This is ignored on WASM platform.
+    pub(crate) fn save_capture(&mut self, filepath: &str) {
+        if cfg!(target_arch = "wasm32") {
+            return;
+        }
+        if let Some(png) = self.capture_screen() {
+            if let Err(e) = std::fs::write(filepath, png) {
+                uni_app::App::print(format!("Could not save screenshot {} : {}\n", filepath, e));
+            }
+        }
+    }
+}
+
+/// When hot reloading is enabled, reload the fonts that have changed on disk
+/// and notify the engine of all the modified files.
+pub(crate) fn reload_assets(engine: &mut dyn Engine, api: &mut DoryenApiImpl, time: f64) {
+    let changed = match api.watcher.as_mut() {
+        Some(watcher) => watcher.poll(time),
+        None => return,
+    };
+    for path in changed {
+        uni_app::App::print(format!("reloading file {}\n", path));
+        if api.current_font.as_deref() == Some(path.as_str()) {
+            api.font_path = Some(path.clone());
+            api.font_errors.remove(&0);
+        }
+        for (id, (font_path, layout)) in api.font_requests.iter() {
+            if *font_path == path {
+                api.new_fonts.push((*id, path.clone(), layout.clone()));
+                api.font_errors.remove(id);
+            }
+        }
+        engine.file_changed(api, &path);
+    }
+}
+
+/// Run the engine update function for every tick elapsed until `time`.
+/// `before_update` is called before each update.
+/// Returns the events produced by the engine. The ticks stop after an [`UpdateEvent::Exit`].
+pub(crate) fn update_ticks(
+    engine: &mut dyn Engine,
+    api: &mut DoryenApiImpl,
+    time: f64,
+    next_tick: &mut f64,
+    before_update: &mut dyn FnMut(&mut DoryenApiImpl),
+) -> Vec<UpdateEvent> {
+    let mut events = Vec::new();
+    let mut skipped_frames: i32 = -1;
+    while time > *next_tick && skipped_frames < MAX_FRAMESKIP {
+        before_update(api);
+        let event = engine.update(api);
+        *next_tick += SKIP_TICKS;
+        skipped_frames += 1;
+        api.input.on_frame();
+        if let Some(event) = event {
+            let exit = matches!(event, UpdateEvent::Exit);
+            events.push(event);
+            if exit {
+                break;
+            }
+        }
+    }
+    if skipped_frames == MAX_FRAMESKIP {
+        *next_tick = time + SKIP_TICKS;
+    }
+    events
 }
 
 /// What is returned by the [`Engine::update`] function
 pub enum UpdateEvent {
-    /// Save a screenshot. parameter = file path.
-    /// The file name must have a .png extension.
-    /// This is ignored on WASM platform.
+    /// Save a screenshot of the root console. parameter = file path.
+    /// The screenshot is a PNG image at the font's native resolution.
+    /// This is ignored on WASM platform. Use [`DoryenApi::capture_screen`] to get the image content instead.
     Capture(String),
     /// end the program
     Exit,
@@ -122,6 +437,9 @@ pub trait Engine {
     /// You can override this method if your game display or logic depends on the window size.
     /// You get the new window size with `api.con().get_screen_size()`. See the resize example
     fn resize(&mut self, _api: &mut dyn DoryenApi) {}
+    /// This is called when a watched file has been modified, if [`AppOptions::hot_reload`] is enabled.
+    /// Fonts, images and xp files have already been reloaded.
+    fn file_changed(&mut self, _api: &mut dyn DoryenApi, _path: &str) {}
 }
 
 #[derive(Debug, Clone)]
@@ -138,6 +456,8 @@ pub struct AppOptions {
     pub window_title: String,
     /// the font to use. See [`DoryenApi::set_font_path`]. Default is 'terminal_8x8.png'
     pub font_path: String,
+    /// how the glyphs are organized in the font. See [`DoryenApi::set_font_layout`]. Default is [`FontLayout::Codepoint`]
+    pub font_layout: FontLayout,
     /// whether framerate are limited by the screen frequency.
     /// On web platforms, this parameter is ignored and vsync is always enabled.
     /// Default is true.
@@ -154,6 +474,10 @@ pub struct AppOptions {
     /// Limit the number of frames per second to lower CPU consumption. Use 0 for unlimited.
     /// Note that if vsync = true, frames per second cannot go over the screen refresh rate.
     pub max_fps: usize,
+    /// Native only. Reload the fonts, images and xp files when they are modified,
+    /// and call [`Engine::file_changed`]. Images and xp files are only watched if they are
+    /// created while an app with hot reloading exists. Default is false.
+    pub hot_reload: bool,
 }
 
 impl Default for AppOptions {
@@ -165,12 +489,14 @@ impl Default for AppOptions {
             screen_height: DEFAULT_CONSOLE_HEIGHT * 8,
             window_title: "".to_owned(),
             font_path: "terminal_8x8.png".to_owned(),
+            font_layout: FontLayout::Codepoint,
             vsync: true,
             fullscreen: false,
             show_cursor: true,
             resizable: true,
             intercept_close_request: false,
             max_fps: 0,
+            hot_reload: false,
         }
     }
 }
@@ -191,11 +517,27 @@ pub struct App {
     char_width: u32,
     char_height: u32,
     screen_resolution: (u32, u32),
+    /// GL viewport : x offset, y offset, width, height
+    viewport: (i32, i32, u32, u32),
+    /// additional fonts, by id
+    fonts: HashMap<usize, GlFont>,
+    /// copy of the layer being rendered in its own pass
+    pass_console: Console,
+}
+
+/// an additional font loaded with [`DoryenApi::add_font`]
+struct GlFont {
+    texture: uni_gl::WebGLTexture,
+    loader: FontLoader,
+    loaded: bool,
+    font_width: u32,
+    font_height: u32,
+    char_width: u32,
+    char_height: u32,
 }
 
 impl App {
     pub fn new(options: AppOptions) -> Self {
-        let con = Console::new(options.console_width, options.console_height);
         let app = uni_app::App::new(uni_app::AppConfig {
             size: (options.screen_width, options.screen_height),
             title: options.window_title.to_owned(),
@@ -258,14 +600,7 @@ impl App {
             font_loader: FontLoader::new(),
             font,
             program,
-            api: DoryenApiImpl {
-                input,
-                con,
-                fps: 0,
-                average_fps: 0,
-                font_path: None,
-                screen_size: (options.screen_width, options.screen_height),
-            },
+            api: DoryenApiImpl::new(&options, input),
             options,
             fps: Fps::new(),
             engine: None,
@@ -274,6 +609,9 @@ impl App {
             char_width: 0,
             char_height: 0,
             screen_resolution,
+            viewport: (x_offset, y_offset, real_screen_width, real_screen_height),
+            fonts: HashMap::new(),
+            pass_console: Console::new(1, 1),
         }
     }
     pub fn set_engine(&mut self, engine: Box<dyn Engine>) {
@@ -282,31 +620,191 @@ impl App {
 
     fn load_font_bytes(&mut self) {
         let img = self.font_loader.img.take().unwrap();
-        if self.font_loader.char_width != 0 {
-            self.char_width = self.font_loader.char_width;
-            self.char_height = self.font_loader.char_height;
-        } else {
-            self.char_width = img.width() as u32 / 16;
-            self.char_height = img.height() as u32 / 16;
-        }
-        self.font_width = img.width() as u32;
-        self.font_height = img.height() as u32;
+        self.char_width = self.font_loader.char_width;
+        self.char_height = self.font_loader.char_height;
+        self.font_width = img.width();
+        self.font_height = img.height();
+        self.api.renderer = Some(SoftwareRenderer::new(
+            img.clone(),
+            self.char_width,
+            self.char_height,
+        ));
         uni_app::App::print(format!(
             "font size: {:?} char size: {:?}",
             (self.font_width, self.font_height),
             (self.char_width, self.char_height)
         ));
-        self.gl.active_texture(0);
-        self.gl.bind_texture(&self.font);
-        self.gl.tex_image2d(
-            uni_gl::TextureBindPoint::Texture2d, // target
-            0,                                   // level
-            img.width() as u16,                  // width
-            img.height() as u16,                 // height
-            uni_gl::PixelFormat::Rgba,           // format
-            uni_gl::PixelType::UnsignedByte,     // type
-            &*img,                               // data
+        upload_font(&self.gl, &self.font, &img);
+        #[cfg(feature = "ttf")]
+        {
+            self.api.ttf = self.font_loader.ttf.take();
+            self.fit_ttf_font();
+        }
+        // the glyph indices in the console textures may change with the font
+        self.api.con.mark_all_dirty();
+    }
+
+    /// rasterize the TrueType font glyphs at the size of a console cell on screen
+    #[cfg(feature = "ttf")]
+    fn fit_ttf_font(&mut self) {
+        if let Some(ttf) = self.api.ttf.as_mut() {
+            let (con_width, con_height) = self.api.con.get_size();
+            ttf.set_char_size(
+                self.viewport.2 / con_width.max(1),
+                self.viewport.3 / con_height.max(1),
+            );
+        }
+    }
+
+    /// upload the TrueType font atlas if new glyphs have been rasterized
+    #[cfg(feature = "ttf")]
+    fn update_ttf_texture(&mut self) {
+        if !std::mem::take(&mut self.api.ttf_updated) {
+            return;
+        }
+        if let Some(ttf) = self.api.ttf.as_ref() {
+            let img = ttf.atlas();
+            (self.char_width, self.char_height) = ttf.char_size();
+            self.font_width = img.width();
+            self.font_height = img.height();
+            upload_font(&self.gl, &self.font, img);
+            self.program.bind(
+                &self.gl,
+                &self.api.con,
+                self.font_width,
+                self.font_height,
+                self.char_width,
+                self.char_height,
+            );
+            self.program
+                .set_texture(&self.gl, uni_gl::WebGLTexture(self.font.0));
+        }
+    }
+
+    /// start loading the fonts added with [`DoryenApi::add_font`] and upload those that are ready
+    fn load_extra_fonts(&mut self) {
+        for (id, font_path, layout) in self.api.new_fonts.drain(..) {
+            let mut loader = FontLoader::new();
+            loader.load_font_with_layout(&font_path, &layout);
+            // a reloaded font keeps its texture
+            let texture = match self.fonts.remove(&id) {
+                Some(font) => font.texture,
+                None => create_texture(&self.gl),
+            };
+            self.fonts.insert(
+                id,
+                GlFont {
+                    texture,
+                    loader,
+                    loaded: false,
+                    font_width: 0,
+                    font_height: 0,
+                    char_width: 0,
+                    char_height: 0,
+                },
+            );
+        }
+        let mut failed = Vec::new();
+        for (id, font) in self.fonts.iter_mut() {
+            if font.loaded {
+                continue;
+            }
+            match font.loader.load_font_async() {
+                Ok(true) => (),
+                Ok(false) => continue,
+                Err(e) => {
+                    uni_app::App::print(format!("{}\n", e));
+                    self.api.font_errors.insert(*id, e);
+                    failed.push(*id);
+                    continue;
+                }
+            }
+            let img = font.loader.img.take().unwrap();
+            font.char_width = font.loader.char_width;
+            font.char_height = font.loader.char_height;
+            font.font_width = img.width();
+            font.font_height = img.height();
+            upload_font(&self.gl, &font.texture, &img);
+            self.api.font_renderers.insert(
+                *id,
+                SoftwareRenderer::new(img, font.char_width, font.char_height),
+            );
+            font.loaded = true;
+        }
+        for id in failed {
+            self.fonts.remove(&id);
+        }
+    }
+
+    /// draw the layers that need their own pass over the root console.
+    /// Each layer is rendered in a viewport matching its position and size in pixels.
+    fn render_layer_passes(&mut self) {
+        if self.api.layers.passes().next().is_none() {
+            return;
+        }
+        let (x_offset, y_offset, width, height) = self.viewport;
+        let (con_width, con_height) = self.api.con.get_size();
+        let scale_x = width as f32 / (con_width * self.char_width) as f32;
+        let scale_y = height as f32 / (con_height * self.char_height) as f32;
+        for layer in self.api.layers.passes() {
+            let (font_width, font_height, char_width, char_height, texture) = match layer.font {
+                0 => (
+                    self.font_width,
+                    self.font_height,
+                    self.char_width,
+                    self.char_height,
+                    &self.font,
+                ),
+                id => match self.fonts.get(&id) {
+                    Some(font) if font.loaded => (
+                        font.font_width,
+                        font.font_height,
+                        font.char_width,
+                        font.char_height,
+                        &font.texture,
+                    ),
+                    _ => continue,
+                },
+            };
+            layer.prepare_pass(&mut self.pass_console);
+            #[cfg(feature = "ttf")]
+            if let (0, Some(ttf)) = (layer.font, self.api.ttf.as_mut()) {
+                ttf.remap(&mut self.pass_console);
+            }
+            let (x, y) = layer.pixel_position(self.char_width, self.char_height);
+            let (layer_width, layer_height) = layer.console.get_size();
+            let pass_width = (layer_width * char_width) as f32 * scale_x;
+            let pass_height = (layer_height * char_height) as f32 * scale_y;
+            // GL viewport origin is the bottom-left corner
+            self.gl.viewport(
+                x_offset + (x as f32 * scale_x) as i32,
+                y_offset + height as i32 - (y as f32 * scale_y + pass_height) as i32,
+                pass_width as u32,
+                pass_height as u32,
+            );
+            self.program.bind(
+                &self.gl,
+                &self.pass_console,
+                font_width,
+                font_height,
+                char_width,
+                char_height,
+            );
+            self.program
+                .set_texture(&self.gl, uni_gl::WebGLTexture(texture.0));
+            self.program.render_pass(&self.gl, &self.pass_console);
+        }
+        self.gl.viewport(x_offset, y_offset, width, height);
+        self.program.bind(
+            &self.gl,
+            &self.api.con,
+            self.font_width,
+            self.font_height,
+            self.char_width,
+            self.char_height,
         );
+        self.program
+            .set_texture(&self.gl, uni_gl::WebGLTexture(self.font.0));
     }
 
     fn resize(
@@ -324,6 +822,9 @@ impl App {
         };
         self.gl
             .viewport(x_offset, y_offset, real_screen_width, real_screen_height);
+        self.viewport = (x_offset, y_offset, real_screen_width, real_screen_height);
+        #[cfg(feature = "ttf")]
+        self.fit_ttf_font();
         self.api.screen_size = (
             (real_screen_width as f32 / hidpi_factor) as u32,
             (real_screen_height as f32 / hidpi_factor) as u32,
@@ -379,53 +880,67 @@ impl App {
             if self.api.font_path.is_some() {
                 let font_path = self.api.font_path.clone().unwrap();
                 self.api.clear_font_path();
-                self.font_loader.load_font(&font_path);
+                self.font_loader
+                    .load_font_with_layout(&font_path, &self.api.font_layout);
                 font_loaded = false;
             }
+            self.load_extra_fonts();
             if !font_loaded {
-                if self.font_loader.load_font_async() {
-                    self.load_font_bytes();
-                    self.program.bind(
-                        &self.gl,
-                        &self.api.con,
-                        self.font_width,
-                        self.font_height,
-                        self.char_width,
-                        self.char_height,
-                    );
-                    self.program
-                        .set_texture(&self.gl, uni_gl::WebGLTexture(self.font.0));
-                    font_loaded = true;
+                match self.font_loader.load_font_async() {
+                    Ok(true) => {
+                        self.load_font_bytes();
+                        self.program.bind(
+                            &self.gl,
+                            &self.api.con,
+                            self.font_width,
+                            self.font_height,
+                            self.char_width,
+                            self.char_height,
+                        );
+                        self.program
+                            .set_texture(&self.gl, uni_gl::WebGLTexture(self.font.0));
+                        font_loaded = true;
+                    }
+                    Ok(false) => (),
+                    Err(e) => {
+                        uni_app::App::print(format!("{}\n", e));
+                        self.api.font_errors.insert(0, e);
+                        if self.font_width == 0 {
+                            // no previous font : render the consoles without glyphs
+                            self.program.bind(&self.gl, &self.api.con, 1, 1, 1, 1);
+                            self.program
+                                .set_texture(&self.gl, uni_gl::WebGLTexture(self.font.0));
+                        }
+                        font_loaded = true;
+                    }
                 }
             } else {
                 self.handle_input(&mut *engine, app.hidpi_factor(), app.events.clone());
-                let mut skipped_frames: i32 = -1;
                 let time = uni_app::now();
-                while time > next_tick && skipped_frames < MAX_FRAMESKIP {
-                    if let Some(event) = engine.update(&mut self.api) {
-                        match event {
-                            UpdateEvent::Capture(filepath) => capture_screen(
-                                &self.gl,
-                                self.options.screen_width,
-                                self.options.screen_height,
-                                &filepath,
-                            ),
-                            UpdateEvent::Exit => uni_app::App::exit(),
-                        }
+                reload_assets(&mut *engine, &mut self.api, time);
+                for event in update_ticks(
+                    &mut *engine,
+                    &mut self.api,
+                    time,
+                    &mut next_tick,
+                    &mut |_| (),
+                ) {
+                    match event {
+                        UpdateEvent::Capture(filepath) => self.api.save_capture(&filepath),
+                        UpdateEvent::Exit => uni_app::App::exit(),
                     }
-                    next_tick += SKIP_TICKS;
-                    skipped_frames += 1;
-                    self.api.input.on_frame();
-                }
-                if skipped_frames == MAX_FRAMESKIP {
-                    next_tick = time + SKIP_TICKS;
                 }
                 if self.options.max_fps == 0 || time > next_frame {
                     engine.render(&mut self.api);
                     self.fps.step();
                     self.api.fps = self.fps.fps();
                     self.api.average_fps = self.fps.average();
-                    self.program.render_primitive(&self.gl, &self.api.con);
+                    self.api.compose_frame();
+                    #[cfg(feature = "ttf")]
+                    self.update_ttf_texture();
+                    self.program.render_primitive(&self.gl, self.api.frame());
+                    self.api.clear_dirty();
+                    self.render_layer_passes();
                     if self.options.max_fps > 0 {
                         next_frame += 1.0 / self.options.max_fps as f64;
                     }
@@ -435,35 +950,6 @@ impl App {
     }
 }
 
-/// This captures an in-game screenshot and saves it to the file
-fn capture_screen(gl: &uni_gl::WebGLRenderingContext, w: u32, h: u32, filepath: &str) {
-    let mut img = image::DynamicImage::new_rgba8(w, h);
-    let pixels = img.as_mut_rgba8().unwrap();
-
-    gl.pixel_storei(uni_gl::PixelStorageMode::PackAlignment, 1);
-    gl.read_pixels(
-        0,
-        0,
-        w,
-        h,
-        uni_gl::PixelFormat::Rgba,
-        uni_gl::PixelType::UnsignedByte,
-        pixels,
-    );
-
-    if cfg!(not(target_arch = "wasm32")) {
-        // disabled on wasm target
-        image::save_buffer(
-            filepath,
-            &image::imageops::flip_vertical(&img),
-            w,
-            h,
-            image::ColorType::Rgba8,
-        )
-        .expect("Failed to save buffer to the specified path");
-    }
-}
-
 fn create_texture(gl: &uni_gl::WebGLRenderingContext) -> uni_gl::WebGLTexture {
     let tex = gl.create_texture();
     gl.active_texture(0);
@@ -472,6 +958,24 @@ fn create_texture(gl: &uni_gl::WebGLRenderingContext) -> uni_gl::WebGLTexture {
     tex
 }
 
+fn upload_font(
+    gl: &uni_gl::WebGLRenderingContext,
+    texture: &uni_gl::WebGLTexture,
+    img: &image::RgbaImage,
+) {
+    gl.active_texture(0);
+    gl.bind_texture(texture);
+    gl.tex_image2d(
+        uni_gl::TextureBindPoint::Texture2d, // target
+        0,                                   // level
+        img.width() as u16,                  // width
+        img.height() as u16,                 // height
+        uni_gl::PixelFormat::Rgba,           // format
+        uni_gl::PixelType::UnsignedByte,     // type
+        img,                                 // data
+    );
+}
+
 struct Fps {
     counter: u32,
     start: f64,
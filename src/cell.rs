@@ -0,0 +1,225 @@
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::color::Color;
+use crate::console::Console;
+
+/// The content of a console cell : a glyph index with its foreground and background colors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub glyph: u32,
+    pub fore: Color,
+    pub back: Color,
+}
+
+impl Cell {
+    pub fn new(glyph: u32, fore: Color, back: Color) -> Self {
+        Self { glyph, fore, back }
+    }
+}
+
+/// A mutable access to the properties of a console cell, returned by [`Console::cells_mut`]
+#[derive(Debug)]
+pub struct CellMut<'a> {
+    pub glyph: &'a mut u32,
+    pub fore: &'a mut Color,
+    pub back: &'a mut Color,
+}
+
+impl<'a> CellMut<'a> {
+    /// a copy of the cell content
+    pub fn get(&self) -> Cell {
+        Cell::new(*self.glyph, *self.fore, *self.back)
+    }
+    /// replace the cell content
+    pub fn set(&mut self, cell: Cell) {
+        *self.glyph = cell.glyph;
+        *self.fore = cell.fore;
+        *self.back = cell.back;
+    }
+}
+
+/// A row of a console, without the padding of the console buffers. See [`Console::row`]
+#[derive(Debug, Clone, Copy)]
+pub struct Row<'a> {
+    pub glyph: &'a [u32],
+    pub fore: &'a [Color],
+    pub back: &'a [Color],
+}
+
+impl<'a> Row<'a> {
+    /// the cell at column x (if x inside the row)
+    pub fn get(&self, x: i32) -> Option<Cell> {
+        let x = usize::try_from(x).ok()?;
+        Some(Cell::new(*self.glyph.get(x)?, self.fore[x], self.back[x]))
+    }
+    /// iterate over the cells of the row, from left to right
+    pub fn iter(&self) -> impl Iterator<Item = Cell> + 'a {
+        let (glyph, fore, back) = (self.glyph, self.fore, self.back);
+        (0..glyph.len()).map(move |x| Cell::new(glyph[x], fore[x], back[x]))
+    }
+}
+
+/// A mutable row of a console, without the padding of the console buffers. See [`Console::rows_mut`]
+#[derive(Debug)]
+pub struct RowMut<'a> {
+    pub glyph: &'a mut [u32],
+    pub fore: &'a mut [Color],
+    pub back: &'a mut [Color],
+}
+
+impl<'a> RowMut<'a> {
+    /// the cell at column x (if x inside the row)
+    pub fn get(&self, x: i32) -> Option<Cell> {
+        let x = usize::try_from(x).ok()?;
+        Some(Cell::new(*self.glyph.get(x)?, self.fore[x], self.back[x]))
+    }
+    /// replace the cell at column x (if x inside the row)
+    pub fn set(&mut self, x: i32, cell: Cell) {
+        if let Ok(x) = usize::try_from(x) {
+            if x < self.glyph.len() {
+                self.glyph[x] = cell.glyph;
+                self.fore[x] = cell.fore;
+                self.back[x] = cell.back;
+            }
+        }
+    }
+    /// iterate over the cells of the row, from left to right
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = CellMut<'_>> {
+        RowMut {
+            glyph: &mut *self.glyph,
+            fore: &mut *self.fore,
+            back: &mut *self.back,
+        }
+        .into_cells()
+    }
+    fn into_cells(self) -> impl Iterator<Item = CellMut<'a>> {
+        self.glyph
+            .iter_mut()
+            .zip(self.fore.iter_mut())
+            .zip(self.back.iter_mut())
+            .map(|((glyph, fore), back)| CellMut { glyph, fore, back })
+    }
+}
+
+impl Console {
+    /// the row y of the console (if y inside the console)
+    ///
+    /// Example
+    /// ```
+    /// use doryen_rs::{Console, TextAlign};
+    /// let mut con = Console::new(20, 5);
+    /// con.print(0, 2, "hello", TextAlign::Left, None, None);
+    /// let row = con.row(2).unwrap();
+    /// assert_eq!(row.glyph.len(), 20);
+    /// let text: String = row.iter().take(5).filter_map(|cell| char::from_u32(cell.glyph)).collect();
+    /// assert_eq!(text, "hello");
+    /// ```
+    pub fn row(&self, y: i32) -> Option<Row<'_>> {
+        let (width, height) = self.get_size();
+        if y < 0 || y as u32 >= height {
+            return None;
+        }
+        let start = y as usize * self.get_pot_width() as usize;
+        let range = start..start + width as usize;
+        Some(Row {
+            glyph: &self.borrow_ascii()[range.clone()],
+            fore: &self.borrow_foreground()[range.clone()],
+            back: &self.borrow_background()[range],
+        })
+    }
+    /// iterate over the rows of the console, from top to bottom
+    pub fn rows(&self) -> impl Iterator<Item = (i32, Row<'_>)> {
+        let height = self.get_size().1 as i32;
+        (0..height).filter_map(move |y| Some((y, self.row(y)?)))
+    }
+    /// iterate over the rows of the console, from top to bottom.
+    /// The whole console is marked as modified.
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = (i32, RowMut<'_>)> {
+        let (width, height) = self.get_size();
+        let (width, height) = (width as usize, height as usize);
+        let pot_width = self.get_pot_width() as usize;
+        self.mark_all_dirty();
+        let (ascii, fore, back) = self.buffers_mut();
+        ascii
+            .chunks_mut(pot_width)
+            .zip(fore.chunks_mut(pot_width))
+            .zip(back.chunks_mut(pot_width))
+            .take(height)
+            .enumerate()
+            .map(move |(y, ((glyph, fore), back))| {
+                (
+                    y as i32,
+                    RowMut {
+                        glyph: &mut glyph[..width],
+                        fore: &mut fore[..width],
+                        back: &mut back[..width],
+                    },
+                )
+            })
+    }
+    /// iterate over the cells of the console with their coordinates, row by row
+    pub fn cells(&self) -> impl Iterator<Item = (i32, i32, Cell)> + '_ {
+        self.rows()
+            .flat_map(|(y, row)| (0..).zip(row.iter()).map(move |(x, cell)| (x, y, cell)))
+    }
+    /// iterate over the cells of the console with their coordinates, row by row.
+    /// The whole console is marked as modified.
+    ///
+    /// Example
+    /// ```
+    /// use doryen_rs::Console;
+    /// let mut con = Console::new(20, 5);
+    /// // checkerboard background
+    /// for (x, y, cell) in con.cells_mut() {
+    ///     if (x + y) % 2 == 0 {
+    ///         *cell.back = (64, 64, 64, 255);
+    ///     }
+    /// }
+    /// assert_eq!(con.get_back(1, 1), Some((64, 64, 64, 255)));
+    /// assert_eq!(con.get_back(1, 2), Some((0, 0, 0, 255)));
+    /// ```
+    pub fn cells_mut(&mut self) -> impl Iterator<Item = (i32, i32, CellMut<'_>)> {
+        self.rows_mut().flat_map(|(y, row)| {
+            (0..)
+                .zip(row.into_cells())
+                .map(move |(x, cell)| (x, y, cell))
+        })
+    }
+    /// parallel version of [`Console::rows_mut`], processing the rows on the rayon thread pool.
+    /// The whole console is marked as modified.
+    #[cfg(feature = "rayon")]
+    pub fn par_rows_mut(&mut self) -> impl IndexedParallelIterator<Item = (i32, RowMut<'_>)> {
+        let (width, height) = self.get_size();
+        let (width, height) = (width as usize, height as usize);
+        let pot_width = self.get_pot_width() as usize;
+        self.mark_all_dirty();
+        let (ascii, fore, back) = self.buffers_mut();
+        ascii
+            .par_chunks_mut(pot_width)
+            .zip(fore.par_chunks_mut(pot_width))
+            .zip(back.par_chunks_mut(pot_width))
+            .take(height)
+            .enumerate()
+            .map(move |(y, ((glyph, fore), back))| {
+                (
+                    y as i32,
+                    RowMut {
+                        glyph: &mut glyph[..width],
+                        fore: &mut fore[..width],
+                        back: &mut back[..width],
+                    },
+                )
+            })
+    }
+    /// parallel version of [`Console::cells_mut`], processing the rows on the rayon thread pool.
+    /// The whole console is marked as modified.
+    #[cfg(feature = "rayon")]
+    pub fn par_cells_mut(&mut self) -> impl ParallelIterator<Item = (i32, i32, CellMut<'_>)> {
+        self.par_rows_mut().flat_map_iter(|(y, row)| {
+            (0..)
+                .zip(row.into_cells())
+                .map(move |(x, cell)| (x, y, cell))
+        })
+    }
+}
@@ -43,3 +43,94 @@ pub fn color_dist(c1: Color, c2: Color) -> i32 {
     let db = i32::from(c1.2) - i32::from(c2.2);
     dr * dr + dg * dg + db * db
 }
+
+/// How a color is combined with the color already present on a console.
+/// This is the same set of operations as libtcod's background flags.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum BlendMode {
+    /// the new color replaces the existing one, alpha is ignored
+    Replace,
+    /// linear interpolation using the new color's alpha
+    #[default]
+    Alpha,
+    /// existing * new. Darkens the existing color, useful for lighting
+    Multiply,
+    /// max(existing, new)
+    Lighten,
+    /// min(existing, new)
+    Darken,
+    /// inverse of multiply. Lightens the existing color
+    Screen,
+    /// existing / (1 - new)
+    ColorDodge,
+    /// 1 - (1 - existing) / new
+    ColorBurn,
+    /// existing + new
+    Add,
+    /// existing + new - 1
+    Burn,
+    /// multiply dark colors and screen light colors
+    Overlay,
+}
+
+impl BlendMode {
+    /// Combine the color `src` with the existing color `dst`.
+    /// `alpha` (0.0 - 1.0) is multiplied by the new color's alpha to interpolate between the
+    /// existing color and the result of the operation. It is ignored in `Replace` mode.
+    ///
+    /// Example
+    /// ```
+    /// use doryen_rs::BlendMode;
+    /// let grey = (128, 128, 128, 255);
+    /// assert_eq!(BlendMode::Multiply.blend((255, 200, 0, 255), grey, 1.0), (128, 100, 0, 255));
+    /// assert_eq!(BlendMode::Add.blend((200, 10, 0, 255), grey, 1.0), (255, 138, 128, 255));
+    /// ```
+    pub fn blend(self, dst: Color, src: Color, alpha: f32) -> Color {
+        if self == BlendMode::Replace {
+            return src;
+        }
+        let alpha = alpha * f32::from(src.3) / 255.0;
+        let channel = |d: u8, s: u8| {
+            let d = f32::from(d) / 255.0;
+            let s = f32::from(s) / 255.0;
+            let value = match self {
+                BlendMode::Replace | BlendMode::Alpha => s,
+                BlendMode::Multiply => d * s,
+                BlendMode::Lighten => d.max(s),
+                BlendMode::Darken => d.min(s),
+                BlendMode::Screen => 1.0 - (1.0 - d) * (1.0 - s),
+                BlendMode::ColorDodge => {
+                    if s < 1.0 {
+                        d / (1.0 - s)
+                    } else {
+                        1.0
+                    }
+                }
+                BlendMode::ColorBurn => {
+                    if s > 0.0 {
+                        1.0 - (1.0 - d) / s
+                    } else {
+                        0.0
+                    }
+                }
+                BlendMode::Add => d + s,
+                BlendMode::Burn => d + s - 1.0,
+                BlendMode::Overlay => {
+                    if d < 0.5 {
+                        2.0 * d * s
+                    } else {
+                        1.0 - 2.0 * (1.0 - d) * (1.0 - s)
+                    }
+                }
+            }
+            .clamp(0.0, 1.0);
+            (((1.0 - alpha) * d + alpha * value) * 255.0 + 0.5) as u8
+        };
+        (
+            channel(dst.0, src.0),
+            channel(dst.1, src.1),
+            channel(dst.2, src.2),
+            255,
+        )
+    }
+}
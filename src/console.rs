@@ -1,6 +1,14 @@
 use std::collections::HashMap;
+use std::ops::{Range, RangeInclusive};
 
-use crate::color::{color_blend, Color};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::cell::Cell;
+use crate::color::{color_blend, BlendMode, Color};
+use crate::draw::DrawTarget;
+use crate::frame::FrameStyle;
+use crate::glyph::{grapheme_width, GlyphMap};
+use crate::view::Viewport;
 
 // rectangle drawing kit
 pub const CHAR_CORNER_NW: u16 = 218;
@@ -18,7 +26,7 @@ pub const CHAR_SUBP_DIAG: u16 = 230;
 pub const CHAR_SUBP_E: u16 = 231;
 pub const CHAR_SUBP_SW: u16 = 232;
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum TextAlign {
     Left,
     Right,
@@ -37,7 +45,12 @@ pub struct Console {
     back: Vec<Color>,
     fore: Vec<Color>,
     colors: HashMap<String, Color>,
-    color_stack: Vec<Color>,
+    glyphs: HashMap<String, u32>,
+    glyph_map: GlyphMap,
+    /// area modified since the last call to clear_dirty : min x, min y, max x, max y (exclusive)
+    dirty: Option<(i32, i32, i32, i32)>,
+    /// area where the drawing methods work. This is the whole console, except while drawing on a [`crate::ConsoleView`]
+    viewport: Viewport,
 }
 
 impl Console {
@@ -69,7 +82,14 @@ impl Console {
             pot_width,
             pot_height,
             colors: HashMap::new(),
-            color_stack: Vec::new(),
+            glyphs: HashMap::new(),
+            glyph_map: GlyphMap::new(),
+            dirty: if width > 0 && height > 0 {
+                Some((0, 0, width as i32, height as i32))
+            } else {
+                None
+            },
+            viewport: Viewport::full(width, height),
         }
     }
     /// resizes the console
@@ -94,6 +114,26 @@ impl Console {
             self.fore.push((255, 255, 255, 255));
             self.ascii.push(' ' as u32);
         }
+        self.viewport = Viewport::full(width, height);
+        self.mark_all_dirty();
+    }
+    /// copy the size and content of another console, reusing this console's buffers.
+    /// If the size doesn't change, the area modified in the other console is added to this console's one.
+    pub(crate) fn copy_from(&mut self, other: &Console) {
+        let resized = self.get_size() != other.get_size();
+        self.width = other.width;
+        self.height = other.height;
+        self.pot_width = other.pot_width;
+        self.pot_height = other.pot_height;
+        self.ascii.clone_from(&other.ascii);
+        self.fore.clone_from(&other.fore);
+        self.back.clone_from(&other.back);
+        self.viewport = Viewport::full(self.width, self.height);
+        if resized {
+            self.mark_all_dirty();
+        } else if let Some((x, y, w, h)) = other.dirty_rect() {
+            self.mark_dirty(x, y, w, h);
+        }
     }
     /// associate a name with a color for this console.
     /// The color name can then be used in [`Console::print_color`]
@@ -107,14 +147,40 @@ impl Console {
     pub fn register_color(&mut self, name: &str, value: Color) {
         self.colors.insert(name.to_owned(), value);
     }
+    /// return the value of a color registered with [`Console::register_color`]
+    pub fn get_color(&self, name: &str) -> Option<Color> {
+        self.colors.get(name).copied()
+    }
+    /// associate a name with a glyph code for this console.
+    /// The glyph can then be inserted in a string with `#[@name]` in [`Console::print_color`]
+    pub fn register_glyph(&mut self, name: &str, code: u32) {
+        self.glyphs.insert(name.to_owned(), code);
+    }
+    /// define how the characters printed with [`Console::print`], [`Console::print_color`] and [`Console::print_rect`]
+    /// are converted into glyph indices. See [`GlyphMap`]
+    pub fn set_glyph_map(&mut self, glyph_map: GlyphMap) {
+        self.glyph_map = glyph_map;
+    }
+    pub fn glyph_map(&self) -> &GlyphMap {
+        &self.glyph_map
+    }
+    pub(crate) fn colors(&self) -> &HashMap<String, Color> {
+        &self.colors
+    }
     pub fn get_width(&self) -> u32 {
-        self.width
+        self.viewport.width
     }
     pub fn get_height(&self) -> u32 {
-        self.height
+        self.viewport.height
     }
     pub fn get_size(&self) -> (u32, u32) {
-        (self.width, self.height)
+        (self.viewport.width, self.viewport.height)
+    }
+    pub(crate) fn viewport(&self) -> Viewport {
+        self.viewport
+    }
+    pub(crate) fn set_viewport(&mut self, viewport: Viewport) {
+        self.viewport = viewport;
     }
     pub fn get_pot_width(&self) -> u32 {
         self.pot_width
@@ -134,18 +200,67 @@ impl Console {
     pub fn borrow_background(&self) -> &Vec<Color> {
         &self.back
     }
-    /// for fast writing of the characters values
+    /// for fast writing of the characters values. The whole console is marked as modified.
     pub fn borrow_mut_ascii(&mut self) -> &mut Vec<u32> {
+        self.mark_all_dirty();
         &mut self.ascii
     }
-    /// for fast writing of the characters colors
+    /// for fast writing of the characters colors. The whole console is marked as modified.
     pub fn borrow_mut_foreground(&mut self) -> &mut Vec<Color> {
+        self.mark_all_dirty();
         &mut self.fore
     }
-    /// for fast writing of the background colors
+    /// for fast writing of the background colors. The whole console is marked as modified.
     pub fn borrow_mut_background(&mut self) -> &mut Vec<Color> {
+        self.mark_all_dirty();
         &mut self.back
     }
+    /// the characters, foreground and background buffers, without marking the console as modified.
+    /// The caller is responsible for calling [`Console::mark_dirty`].
+    pub(crate) fn buffers_mut(&mut self) -> (&mut [u32], &mut [Color], &mut [Color]) {
+        (&mut self.ascii, &mut self.fore, &mut self.back)
+    }
+    /// the rectangle (x, y, width, height) containing all the cells modified since the console was created
+    /// or since the last call to [`Console::clear_dirty`], or None if nothing changed.
+    /// The renderer uses it to upload only the modified rows of the console textures.
+    /// Example
+    /// ```
+    /// use doryen_rs::{Console, TextAlign};
+    /// let mut con = Console::new(80, 25);
+    /// con.clear_dirty();
+    /// con.print(10, 3, "Hello", TextAlign::Left, None, None);
+    /// con.cell(2, 5, Some('@' as u16), None, None);
+    /// assert_eq!(con.dirty_rect(), Some((2, 3, 13, 3)));
+    /// ```
+    pub fn dirty_rect(&self) -> Option<(i32, i32, u32, u32)> {
+        self.dirty
+            .map(|(x0, y0, x1, y1)| (x0, y0, (x1 - x0) as u32, (y1 - y0) as u32))
+    }
+    /// add a rectangle to the modified area, so that the renderer uploads it again
+    pub fn mark_dirty(&mut self, x: i32, y: i32, w: u32, h: u32) {
+        let (clip_x0, clip_y0, clip_x1, clip_y1) = self.viewport.clip;
+        let (origin_x, origin_y) = (self.viewport.x, self.viewport.y);
+        let x0 = x.max(clip_x0) + origin_x;
+        let y0 = y.max(clip_y0) + origin_y;
+        let x1 = x.saturating_add(w as i32).min(clip_x1) + origin_x;
+        let y1 = y.saturating_add(h as i32).min(clip_y1) + origin_y;
+        if x0 >= x1 || y0 >= y1 {
+            return;
+        }
+        self.dirty = Some(match self.dirty {
+            Some((dx0, dy0, dx1, dy1)) => (dx0.min(x0), dy0.min(y0), dx1.max(x1), dy1.max(y1)),
+            None => (x0, y0, x1, y1),
+        });
+    }
+    /// mark the whole console as modified
+    pub fn mark_all_dirty(&mut self) {
+        let (width, height) = self.get_size();
+        self.mark_dirty(0, 0, width, height);
+    }
+    /// forget the modified area. This is done by the renderer once the console has been uploaded.
+    pub fn clear_dirty(&mut self) {
+        self.dirty = None;
+    }
     /// get the background color of a cell (if x,y inside the console)
     pub fn get_back(&self, x: i32, y: i32) -> Option<Color> {
         if self.check_coords(x, y) {
@@ -167,6 +282,42 @@ impl Console {
         }
         None
     }
+    /// get the glyph index of a cell (if x,y inside the console).
+    /// Contrary to [`Console::get_ascii`], this works with glyph indices above 65535.
+    pub fn get_glyph(&self, x: i32, y: i32) -> Option<u32> {
+        if self.check_coords(x, y) {
+            return Some(self.ascii[self.offset(x, y)]);
+        }
+        None
+    }
+    /// get the glyph index and colors of a cell (if x,y inside the console)
+    ///
+    /// Example
+    /// ```
+    /// use doryen_rs::{Cell, Console};
+    /// let mut con = Console::new(80, 25);
+    /// let wall = Cell::new('#' as u32, (200, 200, 200, 255), (40, 40, 40, 255));
+    /// con.set_cell(3, 4, wall);
+    /// assert_eq!(con.get_cell(3, 4), Some(wall));
+    /// assert_eq!(con.get_cell(80, 4), None);
+    /// ```
+    pub fn get_cell(&self, x: i32, y: i32) -> Option<Cell> {
+        if self.check_coords(x, y) {
+            let off = self.offset(x, y);
+            return Some(Cell::new(self.ascii[off], self.fore[off], self.back[off]));
+        }
+        None
+    }
+    /// set the glyph index and colors of a cell (if x,y inside the console)
+    pub fn set_cell(&mut self, x: i32, y: i32, cell: Cell) {
+        if self.check_coords(x, y) {
+            let off = self.offset(x, y);
+            self.mark_dirty(x, y, 1, 1);
+            self.ascii[off] = cell.glyph;
+            self.fore[off] = cell.fore;
+            self.back[off] = cell.back;
+        }
+    }
     /// get the background color of a cell (no boundary check)
     pub fn unsafe_get_back(&self, x: i32, y: i32) -> Color {
         let off = self.offset(x, y);
@@ -182,11 +333,21 @@ impl Console {
         let off = self.offset(x, y);
         self.ascii[off] as u16
     }
-    fn offset(&self, x: i32, y: i32) -> usize {
-        x as usize + y as usize * self.pot_width as usize
+    /// index of a cell in the buffers
+    pub(crate) fn offset(&self, x: i32, y: i32) -> usize {
+        (x + self.viewport.x) as usize + (y + self.viewport.y) as usize * self.pot_width as usize
     }
     fn check_coords(&self, x: i32, y: i32) -> bool {
-        (x as u32) < self.width && (y as u32) < self.height
+        let (x0, y0, x1, y1) = self.viewport.clip;
+        x >= x0 && x < x1 && y >= y0 && y < y1
+    }
+    /// the range of coordinates of a rectangle that are inside the console
+    fn clip_rect(&self, x: i32, y: i32, w: u32, h: u32) -> (Range<i32>, Range<i32>) {
+        let (x0, y0, x1, y1) = self.viewport.clip;
+        (
+            x.max(x0)..x.saturating_add(w as i32).min(x1),
+            y.max(y0)..y.saturating_add(h as i32).min(y1),
+        )
     }
     /// set the character at a specific position (doesn't change the color).
     ///
@@ -201,6 +362,15 @@ impl Console {
             self.unsafe_ascii(x, y, ascii);
         }
     }
+    /// set the glyph index at a specific position (doesn't change the color).
+    /// Contrary to [`Console::ascii`], this works with glyph indices above 65535.
+    pub fn glyph(&mut self, x: i32, y: i32, glyph: u32) {
+        if self.check_coords(x, y) {
+            let off = self.offset(x, y);
+            self.ascii[off] = glyph;
+            self.mark_dirty(x, y, 1, 1);
+        }
+    }
     /// set the character color at a specific position
     pub fn fore(&mut self, x: i32, y: i32, col: Color) {
         if self.check_coords(x, y) {
@@ -217,26 +387,34 @@ impl Console {
     pub fn unsafe_ascii(&mut self, x: i32, y: i32, ascii: u16) {
         let off = self.offset(x, y);
         self.ascii[off] = u32::from(ascii);
+        self.mark_dirty(x, y, 1, 1);
     }
     /// set the character color at a specific position (no boundary check)
     pub fn unsafe_fore(&mut self, x: i32, y: i32, col: Color) {
         let off = self.offset(x, y);
         self.fore[off] = col;
+        self.mark_dirty(x, y, 1, 1);
     }
     /// set the background color at a specific position (no boundary check)
     pub fn unsafe_back(&mut self, x: i32, y: i32, col: Color) {
         let off = self.offset(x, y);
         self.back[off] = col;
+        self.mark_dirty(x, y, 1, 1);
     }
     /// fill the whole console with values
     pub fn clear(&mut self, fore: Option<Color>, back: Option<Color>, fillchar: Option<u16>) {
-        let w = self.width;
-        let h = self.height;
+        let (w, h) = self.get_size();
         self.area(0, 0, w, h, fore, back, fillchar);
     }
-    /// write a multi-color string. Foreground color is defined by #[color_name] patterns inside the string.
-    /// color_name must have been registered with [`Console::register_color`] before.
-    /// Default foreground color is white, at the start of the string.
+    /// write a multi-color string. Colors are defined by #[...] patterns inside the string :
+    /// * `#[color_name]` changes the foreground color. color_name must have been registered with [`Console::register_color`] before.
+    /// * `#[#rrggbb]` or `#[#rrggbbaa]` changes the foreground color to an hexadecimal value.
+    /// * `#[bg:color_name]` or `#[bg:#rrggbb]` changes the background color. `#[bg:]` ends the background color span.
+    /// * `#[@65]` inserts the glyph with code 65. `#[@name]` inserts a glyph registered with [`Console::register_glyph`].
+    ///   Unknown glyph names are displayed as '?'.
+    /// * `#[[` is a literal `#[`.
+    ///
+    /// Default foreground color is white, at the start of the string. Default background color is the `back` parameter.
     /// When an unknown color name is used, the color goes back to its previous value.
     /// You can then use an empty name to end a color span.
     /// Example
@@ -246,6 +424,12 @@ impl Console {
     /// con.register_color("pink", (255, 0, 255, 255));
     /// con.register_color("blue", (0, 0, 255, 255));
     /// con.print_color(5, 5, "#[blue]This blue text contains a #[pink]pink#[] word", TextAlign::Left, None);
+    /// con.register_glyph("heart", 3);
+    /// con.print_color(0, 0, "#[bg:#800000]#[@heart]#[bg:] #[#ff8800]orange#[] #[[", TextAlign::Left, None);
+    /// assert_eq!(con.get_ascii(0, 0), Some(3));
+    /// assert_eq!(con.get_back(0, 0), Some((128, 0, 0, 255)));
+    /// assert_eq!(con.get_fore(2, 0), Some((255, 136, 0, 255)));
+    /// assert_eq!(con.get_ascii(9, 0), Some('#' as u16));
     /// ```
     pub fn print_color(
         &mut self,
@@ -255,12 +439,7 @@ impl Console {
         align: TextAlign,
         back: Option<Color>,
     ) {
-        let mut cury = y;
-        self.color_stack.clear();
-        for line in text.to_owned().split('\n') {
-            self.print_line_color(x, cury, line, align, back);
-            cury += 1;
-        }
+        DrawTarget::print_color(self, x, y, text, align, back);
     }
 
     /// compute the length of a string containing color codes.
@@ -273,74 +452,107 @@ impl Console {
     /// assert_eq!(len, 3); // actual string : "a\nb"
     /// let len = Console::text_color_len("normal string");
     /// assert_eq!(len, 13);
+    /// let len = Console::text_color_len("#[bg:#ff0000]#[@heart]#[bg:] #[[");
+    /// assert_eq!(len, 4); // actual string : "♥ #["
     /// ```
     pub fn text_color_len(text: &str) -> usize {
-        let mut text_len = 0;
-        for color_span in text.to_owned().split("#[") {
-            if color_span.is_empty() {
-                continue;
-            }
-            let mut col_text = color_span.split(']');
-            let col_name = col_text.next().unwrap();
-            if let Some(text_span) = col_text.next() {
-                text_len += text_span.chars().count();
-            } else {
-                text_len += col_name.chars().count();
-            }
-        }
-        text_len
-    }
-
-    fn get_color_spans(&mut self, text: &str, text_len: &mut i32) -> Vec<(Color, String)> {
-        let mut spans: Vec<(Color, String)> = Vec::new();
-        *text_len = 0;
-        let mut fore = *self.color_stack.last().unwrap_or(&(255, 255, 255, 255));
-        for color_span in text.to_owned().split("#[") {
-            if color_span.is_empty() {
-                continue;
-            }
-            let mut col_text = color_span.splitn(2, ']');
-            let col_name = col_text.next().unwrap();
-            if let Some(text_span) = col_text.next() {
-                if let Some(color) = self.colors.get(col_name) {
-                    fore = *color;
-                    self.color_stack.push(fore);
-                } else {
-                    self.color_stack.pop();
-                    fore = *self.color_stack.last().unwrap_or(&(255, 255, 255, 255));
-                }
-                spans.push((fore, text_span.to_owned()));
-                *text_len += text_span.chars().count() as i32;
-            } else {
-                spans.push((fore, col_name.to_owned()));
-                *text_len += col_name.chars().count() as i32;
-            }
-        }
-        spans
+        text_tokens(text)
+            .iter()
+            .map(|(token, _)| token.visible_len())
+            .sum()
     }
 
-    fn print_line_color(
+    /// write a string containing color codes (see [`Console::print_color`]) inside a rectangle.
+    /// Lines are word-wrapped to fit the rectangle width, and the lines that don't fit in the rectangle height are not printed.
+    /// With `h` = 0, the height is only limited by the console's border.
+    /// The alignment is relative to the rectangle : x + w - 1 is the right border for [`TextAlign::Right`],
+    /// x + w / 2 is the center for [`TextAlign::Center`].
+    /// Returns the number of lines printed.
+    /// Example
+    /// ```
+    /// use doryen_rs::{Console, TextAlign};
+    /// let mut con = Console::new(20, 10);
+    /// con.register_color("red", (255, 0, 0, 255));
+    /// let text = "the #[red]quick#[] brown fox jumps";
+    /// let lines = con.print_rect(1, 1, 10, 0, text, TextAlign::Left, None);
+    /// assert_eq!(lines, 3); // "the quick", "brown fox", "jumps"
+    /// assert_eq!(con.get_ascii(1, 2), Some('b' as u16));
+    /// assert_eq!(con.get_fore(5, 1), Some((255, 0, 0, 255)));
+    /// assert_eq!(Console::measure_text(10, text), 3);
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn print_rect(
         &mut self,
         x: i32,
         y: i32,
+        w: u32,
+        h: u32,
         text: &str,
         align: TextAlign,
         back: Option<Color>,
-    ) {
-        let mut str_len = 0;
-        let spans = self.get_color_spans(text, &mut str_len);
-        let mut ix = match align {
-            TextAlign::Left => x,
-            TextAlign::Right => x - str_len + 1,
-            TextAlign::Center => x - str_len / 2,
-        };
-        for (color, span) in spans {
-            self.print_line(ix, y, &span, TextAlign::Left, Some(color), back);
-            ix += span.chars().count() as i32;
+    ) -> u32 {
+        DrawTarget::print_rect(self, x, y, w, h, text, align, back)
+    }
+    /// compute the number of lines needed to print a string containing color codes
+    /// with [`Console::print_rect`] in a rectangle `w` cells wide.
+    pub fn measure_text(w: u32, text: &str) -> u32 {
+        Console::wrap_text(w, text).len() as u32
+    }
+    /// split a text in lines not longer than `width` visible characters.
+    /// Lines are broken on spaces unless a word is longer than the width. Color codes are kept in the lines.
+    pub(crate) fn wrap_text(width: u32, text: &str) -> Vec<String> {
+        let width = width as usize;
+        let mut lines = Vec::new();
+        if width == 0 {
+            return lines;
         }
+        for paragraph in text.split('\n') {
+            let mut line = String::new();
+            let mut line_len = 0;
+            for (i, word) in paragraph.split(' ').enumerate() {
+                let tokens = text_tokens(word);
+                let word_len: usize = tokens.iter().map(|(token, _)| token.visible_len()).sum();
+                if i > 0 {
+                    if line_len + 1 + word_len <= width {
+                        line.push(' ');
+                        line_len += 1;
+                    } else {
+                        lines.push(std::mem::take(&mut line));
+                        line_len = 0;
+                    }
+                }
+                for (token, source) in tokens {
+                    let len = token.visible_len();
+                    // word longer than the width
+                    if len > 0 && line_len > 0 && line_len + len > width {
+                        lines.push(std::mem::take(&mut line));
+                        line_len = 0;
+                    }
+                    line_len += len;
+                    line.push_str(source);
+                }
+            }
+            lines.push(line);
+        }
+        lines
     }
     /// write a string. If the string reaches the border of the console, it's truncated.
     /// If the string contains carriage return `"\n"`, multiple lines are printed.
+    ///
+    /// The string is printed grapheme by grapheme. Each grapheme is converted into a glyph index with the console's
+    /// [`GlyphMap`] using its first character (combining marks are ignored).
+    /// East Asian wide characters use two cells, the second one containing a space.
+    /// Example
+    /// ```
+    /// use doryen_rs::{Console, TextAlign};
+    /// let mut con = Console::new(10, 1);
+    /// con.print(0, 0, "真e\u{301}😀!", TextAlign::Left, None, None);
+    /// assert_eq!(con.get_glyph(0, 0), Some('真' as u32));
+    /// assert_eq!(con.get_glyph(1, 0), Some(' ' as u32));
+    /// assert_eq!(con.get_glyph(2, 0), Some('e' as u32));
+    /// assert_eq!(con.get_glyph(3, 0), Some('😀' as u32));
+    /// assert_eq!(con.get_glyph(5, 0), Some('!' as u32));
+    /// ```
     pub fn print(
         &mut self,
         x: i32,
@@ -350,78 +562,102 @@ impl Console {
         fore: Option<Color>,
         back: Option<Color>,
     ) {
-        let mut cury = y;
-        for line in text.to_owned().split('\n') {
-            self.print_line(x, cury, line, align, fore, back);
-            cury += 1;
-        }
+        DrawTarget::print(self, x, y, text, align, fore, back);
     }
-    fn print_line(
+    /// draw a rectangle, possibly filling it with a character.
+    /// The border uses the CP437 single line glyphs and overwrites the existing ones.
+    /// See [`Console::frame`] for other styles.
+    #[allow(clippy::too_many_arguments)]
+    pub fn rectangle(
         &mut self,
         x: i32,
         y: i32,
-        text: &str,
-        align: TextAlign,
+        w: u32,
+        h: u32,
         fore: Option<Color>,
         back: Option<Color>,
+        fill: Option<u16>,
     ) {
-        let stext = text.to_owned();
-        let mut str_len = stext.chars().count() as i32;
-        let mut start = 0;
-        let mut ix = match align {
-            TextAlign::Left => x,
-            TextAlign::Right => x - str_len + 1,
-            TextAlign::Center => x - str_len / 2,
-        };
-        if ix < 0 {
-            str_len += ix;
-            start -= ix;
-            ix = 0;
-        }
-        if ix + str_len > self.width as i32 {
-            str_len = self.width as i32 - ix;
-        }
-        let mut chars = stext.chars().skip(start as usize);
-        for _ in 0..str_len {
-            let ch = chars.next();
-            self.cell(ix, y, Some(ch.unwrap() as u16), fore, back);
-            ix += 1;
-        }
+        let style = FrameStyle::SINGLE.with_junctions(false);
+        self.frame(x, y, w, h, &style, fore, back, fill);
     }
-    /// draw a rectangle, possibly filling it with a character.
-    pub fn rectangle(
+    /// draw a rectangle with a border style and an optional title, possibly filling it with a character.
+    /// If the style has junctions enabled, borders drawn over existing ones are connected with junction glyphs.
+    /// Example
+    /// ```
+    /// use doryen_rs::{Console, FrameStyle};
+    /// let mut con = Console::new(20, 10);
+    /// con.frame(0, 0, 20, 10, &FrameStyle::SINGLE, None, None, None);
+    /// // split the frame in two
+    /// con.frame(0, 0, 10, 10, &FrameStyle::SINGLE, None, None, None);
+    /// assert_eq!(con.get_glyph(9, 0), Some(194)); // ┬
+    /// assert_eq!(con.get_glyph(9, 9), Some(193)); // ┴
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn frame(
         &mut self,
         x: i32,
         y: i32,
         w: u32,
         h: u32,
+        style: &FrameStyle,
         fore: Option<Color>,
         back: Option<Color>,
         fill: Option<u16>,
     ) {
+        if w == 0 || h == 0 {
+            return;
+        }
         let right = x + (w as i32) - 1;
         let down = y + (h as i32) - 1;
-        self.cell(x, y, Some(CHAR_CORNER_NW), fore, back);
-        self.cell(right, down, Some(CHAR_CORNER_SE), fore, back);
-        self.cell(right, y, Some(CHAR_CORNER_NE), fore, back);
-        self.cell(x, down, Some(CHAR_CORNER_SW), fore, back);
-        if (y as u32) < self.height {
-            self.area(x + 1, y, w - 2, 1, fore, back, Some(CHAR_LINE_H));
-        }
-        if (down as u32) < self.height {
-            self.area(x + 1, down, w - 2, 1, fore, back, Some(CHAR_LINE_H));
-        }
-        if (x as u32) < self.width {
-            self.area(x, y + 1, 1, h - 2, fore, back, Some(CHAR_LINE_V));
-        }
-        if (right as u32) < self.width {
-            self.area(right, y + 1, 1, h - 2, fore, back, Some(CHAR_LINE_V));
+        let [nw, n, ne, west, east, sw, s, se] = style.glyphs;
+        let border = |con: &mut Console, x: i32, y: i32, glyph: u32| {
+            if let Some(existing) = con.get_glyph(x, y) {
+                con.glyph(x, y, style.junction(existing, glyph));
+                con.cell(x, y, None, fore, back);
+            }
+        };
+        // each cell is written once so that the frame doesn't join with itself.
+        // A frame with a single row or column is a plain line.
+        if h == 1 {
+            for ix in x..=right {
+                border(self, ix, y, n);
+            }
+        } else if w == 1 {
+            for iy in y..=down {
+                border(self, x, iy, west);
+            }
+        } else {
+            for ix in x + 1..right {
+                border(self, ix, y, n);
+                border(self, ix, down, s);
+            }
+            for iy in y + 1..down {
+                border(self, x, iy, west);
+                border(self, right, iy, east);
+            }
+            border(self, x, y, nw);
+            border(self, right, y, ne);
+            border(self, x, down, sw);
+            border(self, right, down, se);
         }
-        if fill.is_some() {
+        if fill.is_some() && w > 2 && h > 2 {
             self.area(x + 1, y + 1, w - 2, h - 2, fore, back, fill);
         }
+        if let Some(title) = &style.title {
+            // keep at least one border glyph on each side of the title
+            let max_len = (w as usize).saturating_sub(2);
+            let title: String = title.graphemes(true).take(max_len).collect();
+            let (tx, align) = match style.title_align {
+                TextAlign::Left => (x + 1, TextAlign::Left),
+                TextAlign::Right => (right - 1, TextAlign::Right),
+                TextAlign::Center => (x + w as i32 / 2, TextAlign::Center),
+            };
+            self.print(tx, y, &title, align, fore, back);
+        }
     }
     /// fill an area with values
+    #[allow(clippy::too_many_arguments)]
     pub fn area(
         &mut self,
         x: i32,
@@ -432,32 +668,310 @@ impl Console {
         back: Option<Color>,
         fillchar: Option<u16>,
     ) {
-        let right = x + (w as i32);
-        let down = y + (h as i32);
-        if let Some(fillchar) = fillchar {
-            for iy in y.max(0)..down.min(self.height as i32) {
-                let off = iy * self.pot_width as i32;
-                for ix in x.max(0)..right.min(self.width as i32) {
-                    self.ascii[(off + ix) as usize] = u32::from(fillchar);
+        if fillchar.is_some() || fore.is_some() || back.is_some() {
+            self.mark_dirty(x, y, w, h);
+        }
+        let (xs, ys) = self.clip_rect(x, y, w, h);
+        for iy in ys {
+            for ix in xs.clone() {
+                let off = self.offset(ix, iy);
+                if let Some(fillchar) = fillchar {
+                    self.ascii[off] = u32::from(fillchar);
+                }
+                if let Some(fore) = fore {
+                    self.fore[off] = fore;
+                }
+                if let Some(back) = back {
+                    self.back[off] = back;
+                }
+            }
+        }
+    }
+    /// combine colors with an area of the console using a [`BlendMode`].
+    /// The characters are not modified.
+    /// Example
+    /// ```
+    /// use doryen_rs::{BlendMode, Console};
+    /// let mut con = Console::new(10, 10);
+    /// con.clear(Some((200, 200, 200, 255)), Some((100, 100, 100, 255)), None);
+    /// // darken the left half of the console
+    /// let grey = Some((128, 128, 128, 255));
+    /// con.area_blend(0, 0, 5, 10, grey, grey, BlendMode::Multiply);
+    /// assert_eq!(con.get_back(0, 0), Some((50, 50, 50, 255)));
+    /// assert_eq!(con.get_fore(0, 0), Some((100, 100, 100, 255)));
+    /// assert_eq!(con.get_back(5, 0), Some((100, 100, 100, 255)));
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn area_blend(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        fore: Option<Color>,
+        back: Option<Color>,
+        mode: BlendMode,
+    ) {
+        if fore.is_some() || back.is_some() {
+            self.mark_dirty(x, y, w, h);
+        }
+        let (xs, ys) = self.clip_rect(x, y, w, h);
+        for iy in ys {
+            for ix in xs.clone() {
+                let idx = self.offset(ix, iy);
+                if let Some(fore) = fore {
+                    self.fore[idx] = mode.blend(self.fore[idx], fore, 1.0);
+                }
+                if let Some(back) = back {
+                    self.back[idx] = mode.blend(self.back[idx], back, 1.0);
                 }
             }
         }
-        if let Some(fore) = fore {
-            for iy in y.max(0)..down.min(self.height as i32) {
-                let off = iy * self.pot_width as i32;
-                for ix in x.max(0)..right.min(self.width as i32) {
-                    self.fore[(off + ix) as usize] = fore;
+    }
+    /// draw a line between two cells using Bresenham's algorithm. Cells outside the console are ignored.
+    /// Example
+    /// ```
+    /// use doryen_rs::Console;
+    /// let mut con = Console::new(10, 10);
+    /// con.line(0, 0, 4, 2, Some('*' as u16), None, None);
+    /// assert_eq!(con.get_ascii(2, 1), Some('*' as u16));
+    /// assert_eq!(con.get_ascii(4, 2), Some('*' as u16));
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn line(
+        &mut self,
+        x1: i32,
+        y1: i32,
+        x2: i32,
+        y2: i32,
+        ascii: Option<u16>,
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) {
+        let (x0, y0, x1c, y1c) = self.viewport.clip;
+        let (dx, dy) = (i64::from(x2) - i64::from(x1), i64::from(y2) - i64::from(y1));
+        // one cell per step along the major axis, only for the steps inside the console
+        let x_major = dx.abs() >= dy.abs();
+        let (major, minor, start, other, steps) = if x_major {
+            (dx, dy, x1, y1, visible_steps(x1, dx, x0, x1c))
+        } else {
+            (dy, dx, y1, x1, visible_steps(y1, dy, y0, y1c))
+        };
+        for i in steps {
+            // same cells as the incremental Bresenham algorithm. i128 because the
+            // product can exceed i64 for endpoints at both ends of the i32 range
+            let j = if major == 0 {
+                0
+            } else {
+                let (major, minor) = (i128::from(major.abs()), i128::from(minor.abs()));
+                ((2 * i128::from(i) * minor + major) / (2 * major)) as i64
+            };
+            let a = i64::from(start) + major.signum() * i;
+            let b = i64::from(other) + minor.signum() * j;
+            let (x, y) = if x_major { (a, b) } else { (b, a) };
+            self.cell(x as i32, y as i32, ascii, fore, back);
+        }
+    }
+    /// draw the outline of a circle
+    pub fn circle(
+        &mut self,
+        x: i32,
+        y: i32,
+        radius: u32,
+        ascii: Option<u16>,
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) {
+        self.ellipse(x, y, radius, radius, ascii, fore, back);
+    }
+    /// draw a filled circle
+    pub fn fill_circle(
+        &mut self,
+        x: i32,
+        y: i32,
+        radius: u32,
+        ascii: Option<u16>,
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) {
+        self.fill_ellipse(x, y, radius, radius, ascii, fore, back);
+    }
+    /// draw the outline of an ellipse centered on (x, y) with horizontal radius `rx` and vertical radius `ry`.
+    /// The outline cells are connected horizontally or vertically.
+    /// Example
+    /// ```
+    /// use doryen_rs::Console;
+    /// let mut con = Console::new(11, 11);
+    /// con.circle(5, 5, 3, Some('o' as u16), None, None);
+    /// assert_eq!(con.get_ascii(5, 2), Some('o' as u16));
+    /// assert_eq!(con.get_ascii(8, 5), Some('o' as u16));
+    /// assert_eq!(con.get_ascii(5, 5), Some(' ' as u16));
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn ellipse(
+        &mut self,
+        x: i32,
+        y: i32,
+        rx: u32,
+        ry: u32,
+        ascii: Option<u16>,
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) {
+        let half_width = |dy: i64| ellipse_half_width(rx, ry, dy);
+        let (x0, y0, x1, y1) = self.viewport.clip;
+        for dy in visible_offsets(y, i64::from(ry), y0, y1) {
+            let width = half_width(dy);
+            // a cell is on the outline if one of its neighbours is outside the ellipse
+            let inner = width.min(half_width(dy - 1)).min(half_width(dy + 1));
+            for dx in visible_offsets(x, width, x0, x1) {
+                if dx.abs() == width || dx.abs() > inner {
+                    self.cell(
+                        (i64::from(x) + dx) as i32,
+                        (i64::from(y) + dy) as i32,
+                        ascii,
+                        fore,
+                        back,
+                    );
                 }
             }
         }
-        if let Some(back) = back {
-            for iy in y.max(0)..down.min(self.height as i32) {
-                let off = iy * self.pot_width as i32;
-                for ix in x.max(0)..right.min(self.width as i32) {
-                    self.back[(off + ix) as usize] = back;
+    }
+    /// draw a filled ellipse centered on (x, y) with horizontal radius `rx` and vertical radius `ry`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn fill_ellipse(
+        &mut self,
+        x: i32,
+        y: i32,
+        rx: u32,
+        ry: u32,
+        ascii: Option<u16>,
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) {
+        let (x0, y0, x1, y1) = self.viewport.clip;
+        for dy in visible_offsets(y, i64::from(ry), y0, y1) {
+            let width = ellipse_half_width(rx, ry, dy);
+            for dx in visible_offsets(x, width, x0, x1) {
+                self.cell(
+                    (i64::from(x) + dx) as i32,
+                    (i64::from(y) + dy) as i32,
+                    ascii,
+                    fore,
+                    back,
+                );
+            }
+        }
+    }
+    /// draw the outline of a polygon. The last point is connected to the first one.
+    pub fn polygon(
+        &mut self,
+        points: &[(i32, i32)],
+        ascii: Option<u16>,
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) {
+        for (i, &(x1, y1)) in points.iter().enumerate() {
+            let (x2, y2) = points[(i + 1) % points.len()];
+            self.line(x1, y1, x2, y2, ascii, fore, back);
+        }
+    }
+    /// draw a filled polygon, including its outline. Self-intersecting polygons use the even-odd rule.
+    /// Example
+    /// ```
+    /// use doryen_rs::Console;
+    /// let mut con = Console::new(10, 10);
+    /// con.fill_polygon(&[(1, 1), (8, 1), (1, 8)], Some('#' as u16), None, None);
+    /// assert_eq!(con.get_ascii(2, 2), Some('#' as u16));
+    /// assert_eq!(con.get_ascii(7, 7), Some(' ' as u16));
+    /// ```
+    pub fn fill_polygon(
+        &mut self,
+        points: &[(i32, i32)],
+        ascii: Option<u16>,
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) {
+        if points.is_empty() {
+            return;
+        }
+        let min_y = points
+            .iter()
+            .map(|p| p.1)
+            .min()
+            .unwrap()
+            .max(self.viewport.clip.1);
+        let max_y = points
+            .iter()
+            .map(|p| p.1)
+            .max()
+            .unwrap()
+            .min(self.viewport.clip.3 - 1);
+        let mut crossings = Vec::new();
+        for y in min_y..=max_y {
+            // x coordinates where the edges cross the cells center row
+            crossings.clear();
+            let yc = y as f32;
+            for (i, &(x1, y1)) in points.iter().enumerate() {
+                let (x2, y2) = points[(i + 1) % points.len()];
+                if (y1 as f32 <= yc) != (y2 as f32 <= yc) {
+                    let t = (yc - y1 as f32) / (y2 as f32 - y1 as f32);
+                    crossings.push(x1 as f32 + t * (x2 as f32 - x1 as f32));
+                }
+            }
+            crossings.sort_by(|a, b| a.total_cmp(b));
+            let (min_x, max_x) = (self.viewport.clip.0, self.viewport.clip.2 - 1);
+            for span in crossings.chunks_exact(2) {
+                for x in (span[0].ceil() as i32).max(min_x)..=(span[1].floor() as i32).min(max_x) {
+                    self.cell(x, y, ascii, fore, back);
                 }
             }
         }
+        self.polygon(points, ascii, fore, back);
+    }
+    /// fill the region of cells connected horizontally or vertically to (x, y) for which
+    /// `matches(glyph, foreground, background)` returns true.
+    /// Returns the number of cells modified.
+    /// Example
+    /// ```
+    /// use doryen_rs::Console;
+    /// let mut con = Console::new(10, 10);
+    /// con.clear(None, None, Some('.' as u16));
+    /// // a wall splitting the console
+    /// con.line(5, 0, 5, 9, Some('#' as u16), None, None);
+    /// let filled = con.flood_fill(0, 0, |glyph, _, _| glyph == '.' as u32, Some('~' as u16), None, None);
+    /// assert_eq!(filled, 50);
+    /// assert_eq!(con.get_ascii(4, 9), Some('~' as u16));
+    /// assert_eq!(con.get_ascii(6, 0), Some('.' as u16));
+    /// ```
+    pub fn flood_fill<F: Fn(u32, Color, Color) -> bool>(
+        &mut self,
+        x: i32,
+        y: i32,
+        matches: F,
+        ascii: Option<u16>,
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) -> u32 {
+        let (width, height) = self.get_size();
+        let mut visited = vec![false; (width * height) as usize];
+        let mut stack = vec![(x, y)];
+        let mut count = 0;
+        while let Some((x, y)) = stack.pop() {
+            if !self.check_coords(x, y) {
+                continue;
+            }
+            let idx = (x + y * width as i32) as usize;
+            let off = self.offset(x, y);
+            if visited[idx] || !matches(self.ascii[off], self.fore[off], self.back[off]) {
+                continue;
+            }
+            visited[idx] = true;
+            self.cell(x, y, ascii, fore, back);
+            count += 1;
+            stack.extend_from_slice(&[(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)]);
+        }
+        count
     }
     /// can change all properties of a console cell at once
     pub fn cell(
@@ -470,6 +984,7 @@ impl Console {
     ) {
         if self.check_coords(x, y) {
             let off = self.offset(x, y);
+            self.mark_dirty(x, y, 1, 1);
             if let Some(ascii) = ascii {
                 self.ascii[off] = u32::from(ascii);
             }
@@ -481,6 +996,30 @@ impl Console {
             }
         }
     }
+    /// same as [`Console::cell`], but the colors are combined with the existing ones using a [`BlendMode`]
+    pub fn cell_blend(
+        &mut self,
+        x: i32,
+        y: i32,
+        ascii: Option<u16>,
+        fore: Option<Color>,
+        back: Option<Color>,
+        mode: BlendMode,
+    ) {
+        if self.check_coords(x, y) {
+            let off = self.offset(x, y);
+            self.mark_dirty(x, y, 1, 1);
+            if let Some(ascii) = ascii {
+                self.ascii[off] = u32::from(ascii);
+            }
+            if let Some(fore) = fore {
+                self.fore[off] = mode.blend(self.fore[off], fore, 1.0);
+            }
+            if let Some(back) = back {
+                self.back[off] = mode.blend(self.back[off], back, 1.0);
+            }
+        }
+    }
     /// blit (draw) a console onto another one
     /// You can use fore_alpha and back_alpha to blend this console with existing background on the destination.
     /// If you define a key color, the cells using this color as background will be ignored. This makes it possible to blit
@@ -497,8 +1036,8 @@ impl Console {
         self.blit_ex(
             0,
             0,
-            self.width as i32,
-            self.height as i32,
+            self.get_width() as i32,
+            self.get_height() as i32,
             destination,
             x,
             y,
@@ -509,6 +1048,7 @@ impl Console {
     }
     /// blit a region of this console onto another one.
     /// see [`Console::blit`]
+    #[allow(clippy::too_many_arguments)]
     pub fn blit_ex(
         &self,
         xsrc: i32,
@@ -522,15 +1062,19 @@ impl Console {
         back_alpha: f32,
         key_color: Option<Color>,
     ) {
+        destination.mark_dirty(
+            xdst,
+            ydst,
+            (wsrc - xsrc).max(0) as u32,
+            (hsrc - ysrc).max(0) as u32,
+        );
         for y in 0..hsrc - ysrc {
-            let off = (y + ysrc) * self.pot_width as i32;
-            let doff = (y + ydst) * destination.pot_width as i32;
             for x in 0..wsrc - xsrc {
                 if self.check_coords(xsrc + x, ysrc + y)
                     && destination.check_coords(xdst + x, ydst + y)
                 {
-                    let src_idx = (off + x + xsrc) as usize;
-                    let dest_idx = (doff + x + xdst) as usize;
+                    let src_idx = self.offset(xsrc + x, ysrc + y);
+                    let dest_idx = destination.offset(xdst + x, ydst + y);
                     let src_back = self.back[src_idx];
                     let dst_back = destination.back[dest_idx];
                     if back_alpha > 0.0 {
@@ -576,4 +1120,276 @@ impl Console {
             }
         }
     }
+    /// blit a console onto another one, combining the colors with a [`BlendMode`] :
+    /// * the background color is combined with the destination background
+    /// * cells containing a space keep the destination character, whose color is combined with this console's background.
+    ///   A lighting or fog-of-war console then affects both the background and the characters below it.
+    /// * other characters are drawn over the destination, their color being interpolated with the new background using fore_alpha
+    ///
+    /// Contrary to [`Console::blit`], [`BlendMode::Alpha`] follows the same rules as the other modes.
+    ///
+    /// Example
+    /// ```
+    /// use doryen_rs::{BlendMode, Console};
+    /// let mut light = Console::new(4, 4);
+    /// light.clear(None, Some((255, 128, 0, 255)), None);
+    /// let mut map = Console::new(4, 4);
+    /// map.clear(Some((200, 200, 200, 255)), Some((100, 100, 100, 255)), Some('#' as u16));
+    /// light.blit_blend(0, 0, &mut map, 1.0, 1.0, None, BlendMode::Multiply);
+    /// assert_eq!(map.get_ascii(0, 0), Some('#' as u16));
+    /// assert_eq!(map.get_back(0, 0), Some((100, 50, 0, 255)));
+    /// assert_eq!(map.get_fore(0, 0), Some((200, 100, 0, 255)));
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn blit_blend(
+        &self,
+        x: i32,
+        y: i32,
+        destination: &mut Console,
+        fore_alpha: f32,
+        back_alpha: f32,
+        key_color: Option<Color>,
+        mode: BlendMode,
+    ) {
+        let (width, height) = self.get_size();
+        self.blit_ex_blend(
+            0,
+            0,
+            width,
+            height,
+            destination,
+            x,
+            y,
+            fore_alpha,
+            back_alpha,
+            key_color,
+            mode,
+        );
+    }
+    /// blit the region of this console starting at xsrc,ysrc with size wsrc x hsrc onto another one,
+    /// combining the colors with a [`BlendMode`]. See [`Console::blit_blend`]
+    ///
+    /// Example
+    /// ```
+    /// use doryen_rs::{BlendMode, Console};
+    /// let mut fog = Console::new(10, 10);
+    /// fog.clear(None, Some((0, 0, 0, 255)), None);
+    /// let mut map = Console::new(10, 10);
+    /// map.clear(None, Some((200, 200, 200, 255)), None);
+    /// // darken a 2x2 square at 5,5 with the 2x2 square at 1,1 of the fog console
+    /// fog.blit_ex_blend(1, 1, 2, 2, &mut map, 5, 5, 1.0, 0.5, None, BlendMode::Alpha);
+    /// assert_eq!(map.get_back(5, 5), Some((100, 100, 100, 255)));
+    /// assert_eq!(map.get_back(7, 5), Some((200, 200, 200, 255)));
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn blit_ex_blend(
+        &self,
+        xsrc: i32,
+        ysrc: i32,
+        wsrc: u32,
+        hsrc: u32,
+        destination: &mut Console,
+        xdst: i32,
+        ydst: i32,
+        fore_alpha: f32,
+        back_alpha: f32,
+        key_color: Option<Color>,
+        mode: BlendMode,
+    ) {
+        destination.mark_dirty(xdst, ydst, wsrc, hsrc);
+        let (dst_x, dst_y) = destination.clip_rect(xdst, ydst, wsrc, hsrc);
+        for dy in dst_y {
+            for dx in dst_x.clone() {
+                let (sx, sy) = (dx - xdst + xsrc, dy - ydst + ysrc);
+                if !self.check_coords(sx, sy) {
+                    continue;
+                }
+                let src_idx = self.offset(sx, sy);
+                if Some(self.back[src_idx]) == key_color {
+                    continue;
+                }
+                let dst_idx = destination.offset(dx, dy);
+                destination.blend_cell(dst_idx, self, src_idx, mode, fore_alpha, back_alpha);
+            }
+        }
+    }
+    /// combine the cell at offset `src_idx` of another console with the cell at offset `idx`. See [`Console::blit_blend`].
+    /// The caller must mark the cell as modified.
+    pub(crate) fn blend_cell(
+        &mut self,
+        idx: usize,
+        src: &Console,
+        src_idx: usize,
+        mode: BlendMode,
+        fore_alpha: f32,
+        back_alpha: f32,
+    ) {
+        let ascii = src.ascii[src_idx];
+        let back = src.back[src_idx];
+        self.back[idx] = mode.blend(self.back[idx], back, back_alpha);
+        if ascii == ' ' as u32 || ascii == 0 {
+            self.fore[idx] = mode.blend(self.fore[idx], back, back_alpha);
+        } else {
+            self.ascii[idx] = ascii;
+            self.fore[idx] = BlendMode::Alpha.blend(self.back[idx], src.fore[src_idx], fore_alpha);
+        }
+    }
+}
+
+impl DrawTarget for Console {
+    fn get_size(&self) -> (u32, u32) {
+        Console::get_size(self)
+    }
+    fn get_cell(&self, x: i32, y: i32) -> Option<Cell> {
+        Console::get_cell(self, x, y)
+    }
+    fn put_cell(
+        &mut self,
+        x: i32,
+        y: i32,
+        glyph: Option<u32>,
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) {
+        if self.check_coords(x, y) {
+            let off = self.offset(x, y);
+            self.mark_dirty(x, y, 1, 1);
+            if let Some(glyph) = glyph {
+                self.ascii[off] = glyph;
+            }
+            if let Some(fore) = fore {
+                self.fore[off] = fore;
+            }
+            if let Some(back) = back {
+                self.back[off] = back;
+            }
+        }
+    }
+    fn map_char(&self, ch: char) -> u32 {
+        self.glyph_map.get(ch)
+    }
+    fn named_color(&self, name: &str) -> Option<Color> {
+        self.colors.get(name).copied()
+    }
+    fn named_glyph(&self, name: &str) -> Option<u32> {
+        self.glyphs.get(name).copied()
+    }
+    fn rectangle(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        fore: Option<Color>,
+        back: Option<Color>,
+        fill: Option<u16>,
+    ) {
+        Console::rectangle(self, x, y, w, h, fore, back, fill);
+    }
+    fn area(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        fore: Option<Color>,
+        back: Option<Color>,
+        fillchar: Option<u16>,
+    ) {
+        Console::area(self, x, y, w, h, fore, back, fillchar);
+    }
+    fn clear(&mut self, fore: Option<Color>, back: Option<Color>, fillchar: Option<u16>) {
+        Console::clear(self, fore, back, fillchar);
+    }
+}
+
+/// an element of a string containing color codes
+pub(crate) enum TextToken<'a> {
+    /// a grapheme (a character, possibly with combining marks) displayed as is
+    Grapheme(&'a str),
+    /// a literal text produced by an escape sequence
+    Text(&'static str),
+    /// `#[color]` : start (or end if the color is unknown) a foreground color span
+    Fore(&'a str),
+    /// `#[bg:color]` : start (or end if the color is unknown) a background color span
+    Back(&'a str),
+    /// `#[@code]` or `#[@name]` : a single glyph
+    Glyph(&'a str),
+}
+
+impl<'a> TextToken<'a> {
+    /// number of cells used to display this token
+    pub(crate) fn visible_len(&self) -> usize {
+        match self {
+            TextToken::Grapheme(grapheme) => grapheme_width(grapheme),
+            TextToken::Glyph(_) => 1,
+            TextToken::Text(text) => text.graphemes(true).map(grapheme_width).sum(),
+            TextToken::Fore(_) | TextToken::Back(_) => 0,
+        }
+    }
+}
+
+/// split a string containing color codes into tokens, each one with the part of the string it comes from
+pub(crate) fn text_tokens(text: &str) -> Vec<(TextToken<'_>, &str)> {
+    let mut tokens = Vec::new();
+    let mut rest = text;
+    while let Some(grapheme) = rest.graphemes(true).next() {
+        let (token, len) = if rest.starts_with("#[[") {
+            (TextToken::Text("#["), 3)
+        } else if let Some(end) = rest.find(']').filter(|_| rest.starts_with("#[")) {
+            let code = &rest[2..end];
+            let token = if let Some(glyph) = code.strip_prefix('@') {
+                TextToken::Glyph(glyph)
+            } else if let Some(color) = code.strip_prefix("bg:") {
+                TextToken::Back(color)
+            } else {
+                TextToken::Fore(code)
+            };
+            (token, end + 1)
+        } else {
+            (TextToken::Grapheme(grapheme), grapheme.len())
+        };
+        tokens.push((token, &rest[..len]));
+        rest = &rest[len..];
+    }
+    tokens
+}
+
+/// parse a rrggbb or rrggbbaa hexadecimal color
+pub(crate) fn parse_hex_color(hex: &str) -> Option<Color> {
+    if !hex.is_ascii() || (hex.len() != 6 && hex.len() != 8) {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    let alpha = if hex.len() == 8 { channel(6)? } else { 255 };
+    Some((channel(0)?, channel(2)?, channel(4)?, alpha))
+}
+
+/// horizontal half width of the row dy of an ellipse centered on row 0, -1 if the row is outside the ellipse
+fn ellipse_half_width(rx: u32, ry: u32, dy: i64) -> i64 {
+    if dy.abs() > i64::from(ry) {
+        return -1;
+    }
+    if ry == 0 {
+        return i64::from(rx);
+    }
+    // the cells centers are inside an ellipse half a cell larger than the radius
+    let t = dy as f32 / (ry as f32 + 0.5);
+    ((rx as f32 + 0.5) * (1.0 - t * t).max(0.0).sqrt()) as i64
+}
+
+/// the offsets in -radius..=radius that keep center + offset inside min..max
+fn visible_offsets(center: i32, radius: i64, min: i32, max: i32) -> RangeInclusive<i64> {
+    let center = i64::from(center);
+    (-radius).max(i64::from(min) - center)..=radius.min(i64::from(max) - 1 - center)
+}
+
+/// the steps i in 0..=|delta| of a line starting at start for which start + i * sign(delta) is inside min..max
+fn visible_steps(start: i32, delta: i64, min: i32, max: i32) -> RangeInclusive<i64> {
+    let (start, min, max) = (i64::from(start), i64::from(min), i64::from(max));
+    if delta >= 0 {
+        (min - start).max(0)..=delta.min(max - 1 - start)
+    } else {
+        (start - (max - 1)).max(0)..=(-delta).min(start - min)
+    }
 }
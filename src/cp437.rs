@@ -0,0 +1,45 @@
+/// The unicode characters corresponding to the 256 glyphs of the CP437 code page
+/// used by most roguelike fonts (including the default `terminal_8x8.png` font).
+/// Control codes 1 to 31 are mapped to their graphical glyph (☺, ☻, ♥, ...).
+pub const CP437_TO_UNICODE: [char; 256] = [
+    ' ', '☺', '☻', '♥', '♦', '♣', '♠', '•', '◘', '○', '◙', '♂', '♀', '♪', '♫', '☼', //
+    '►', '◄', '↕', '‼', '¶', '§', '▬', '↨', '↑', '↓', '→', '←', '∟', '↔', '▲', '▼', //
+    ' ', '!', '"', '#', '$', '%', '&', '\'', '(', ')', '*', '+', ',', '-', '.', '/', //
+    '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', ':', ';', '<', '=', '>', '?', //
+    '@', 'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M', 'N', 'O', //
+    'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', '[', '\\', ']', '^', '_', //
+    '`', 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o', //
+    'p', 'q', 'r', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z', '{', '|', '}', '~', '⌂', //
+    'Ç', 'ü', 'é', 'â', 'ä', 'à', 'å', 'ç', 'ê', 'ë', 'è', 'ï', 'î', 'ì', 'Ä', 'Å', //
+    'É', 'æ', 'Æ', 'ô', 'ö', 'ò', 'û', 'ù', 'ÿ', 'Ö', 'Ü', '¢', '£', '¥', '₧', 'ƒ', //
+    'á', 'í', 'ó', 'ú', 'ñ', 'Ñ', 'ª', 'º', '¿', '⌐', '¬', '½', '¼', '¡', '«', '»', //
+    '░', '▒', '▓', '│', '┤', '╡', '╢', '╖', '╕', '╣', '║', '╗', '╝', '╜', '╛', '┐', //
+    '└', '┴', '┬', '├', '─', '┼', '╞', '╟', '╚', '╔', '╩', '╦', '╠', '═', '╬', '╧', //
+    '╨', '╤', '╥', '╙', '╘', '╒', '╓', '╫', '╪', '┘', '┌', '█', '▄', '▌', '▐', '▀', //
+    'α', 'ß', 'Γ', 'π', 'Σ', 'σ', 'µ', 'τ', 'Φ', 'Θ', 'Ω', 'δ', '∞', 'φ', 'ε', '∩', //
+    '≡', '±', '≥', '≤', '⌠', '⌡', '÷', '≈', '°', '∙', '·', '√', 'ⁿ', '²', '■', '\u{a0}',
+];
+
+/// convert a CP437 code to the corresponding unicode character
+pub fn cp437_to_char(code: u8) -> char {
+    CP437_TO_UNICODE[code as usize]
+}
+
+/// convert a unicode character to its CP437 code, if it exists in the code page
+/// Example
+/// ```
+/// use doryen_rs::char_to_cp437;
+/// assert_eq!(char_to_cp437('A'), Some(65));
+/// assert_eq!(char_to_cp437('┼'), Some(197));
+/// assert_eq!(char_to_cp437('€'), None);
+/// ```
+pub fn char_to_cp437(ch: char) -> Option<u8> {
+    if (' '..='~').contains(&ch) {
+        return Some(ch as u8);
+    }
+    CP437_TO_UNICODE
+        .iter()
+        .skip(1)
+        .position(|&c| c == ch)
+        .map(|pos| pos as u8 + 1)
+}
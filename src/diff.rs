@@ -0,0 +1,104 @@
+use crate::color::Color;
+use crate::console::Console;
+
+/// The new content of a cell that differs between two consoles. See [`Console::diff`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellChange {
+    pub x: i32,
+    pub y: i32,
+    pub glyph: u32,
+    pub fore: Color,
+    pub back: Color,
+}
+
+impl Console {
+    /// iterate over the cells of this console that are different in `previous`, row by row.
+    /// Rows that are identical in both consoles are skipped without checking every cell.
+    /// If the consoles don't have the same size, all the cells of this console are returned.
+    ///
+    /// Example
+    /// ```
+    /// use doryen_rs::{Console, TextAlign};
+    /// let previous = Console::new(20, 5);
+    /// let mut current = Console::new(20, 5);
+    /// current.print(2, 1, "hi", TextAlign::Left, None, None);
+    /// let changes: Vec<_> = current.diff(&previous).collect();
+    /// assert_eq!(changes.len(), 2);
+    /// assert_eq!((changes[0].x, changes[0].y, changes[0].glyph), (2, 1, 'h' as u32));
+    ///
+    /// // replay the changes on another console
+    /// let mut mirror = Console::new(20, 5);
+    /// mirror.apply_diff(changes);
+    /// assert_eq!(current.diff(&mirror).count(), 0);
+    /// ```
+    pub fn diff<'a>(&'a self, previous: &'a Console) -> impl Iterator<Item = CellChange> + 'a {
+        let same_size = self.get_size() == previous.get_size();
+        let (width, height) = self.get_size();
+        let pot_width = self.get_pot_width() as usize;
+        (0..height as usize)
+            .filter(move |&y| !same_size || !self.same_row(previous, y))
+            .flat_map(move |y| {
+                (0..width as usize).filter_map(move |x| {
+                    let off = x + y * pot_width;
+                    let cell = (
+                        self.borrow_ascii()[off],
+                        self.borrow_foreground()[off],
+                        self.borrow_background()[off],
+                    );
+                    if same_size
+                        && cell
+                            == (
+                                previous.borrow_ascii()[off],
+                                previous.borrow_foreground()[off],
+                                previous.borrow_background()[off],
+                            )
+                    {
+                        return None;
+                    }
+                    Some(CellChange {
+                        x: x as i32,
+                        y: y as i32,
+                        glyph: cell.0,
+                        fore: cell.1,
+                        back: cell.2,
+                    })
+                })
+            })
+    }
+    /// write the cells returned by [`Console::diff`] in this console. Cells outside the console are ignored.
+    pub fn apply_diff<I: IntoIterator<Item = CellChange>>(&mut self, changes: I) {
+        let (width, height) = self.get_size();
+        let pot_width = self.get_pot_width() as usize;
+        let mut dirty: Option<(i32, i32, i32, i32)> = None;
+        let (ascii, fore, back) = self.buffers_mut();
+        for change in changes {
+            if (change.x as u32) >= width || (change.y as u32) >= height {
+                continue;
+            }
+            let off = change.x as usize + change.y as usize * pot_width;
+            ascii[off] = change.glyph;
+            fore[off] = change.fore;
+            back[off] = change.back;
+            dirty = Some(match dirty {
+                Some((x0, y0, x1, y1)) => (
+                    x0.min(change.x),
+                    y0.min(change.y),
+                    x1.max(change.x),
+                    y1.max(change.y),
+                ),
+                None => (change.x, change.y, change.x, change.y),
+            });
+        }
+        if let Some((x0, y0, x1, y1)) = dirty {
+            self.mark_dirty(x0, y0, (x1 - x0 + 1) as u32, (y1 - y0 + 1) as u32);
+        }
+    }
+    /// whether a row has the same content in both consoles, which must have the same size
+    fn same_row(&self, other: &Console, y: usize) -> bool {
+        let start = y * self.get_pot_width() as usize;
+        let row = start..start + self.get_width() as usize;
+        self.borrow_ascii()[row.clone()] == other.borrow_ascii()[row.clone()]
+            && self.borrow_foreground()[row.clone()] == other.borrow_foreground()[row.clone()]
+            && self.borrow_background()[row.clone()] == other.borrow_background()[row]
+    }
+}
@@ -18,7 +18,7 @@ void main(){
     vec2 address = floor(vTextureCoord) * uTermCoef + vec2(0.001, 0.001);
     // get the u32 ascii code from the ascii texture
     vec4 ascii_vec = texture(uAscii, address);
-    float ascii_code = (ascii_vec.r * 255.0) + (ascii_vec.g * 255.0 * 256.0);
+    float ascii_code = (ascii_vec.r * 255.0) + (ascii_vec.g * 255.0 * 256.0) + (ascii_vec.b * 255.0 * 65536.0);
     // get the foreground and background colors
     vec4 foreground = texture(uFront, address);
     vec4 background = texture(uBack, address);
@@ -0,0 +1,314 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::cell::Cell;
+use crate::color::Color;
+use crate::console::{
+    parse_hex_color, text_tokens, Console, TextAlign, TextToken, CHAR_CORNER_NE, CHAR_CORNER_NW,
+    CHAR_CORNER_SE, CHAR_CORNER_SW, CHAR_LINE_H, CHAR_LINE_V,
+};
+use crate::glyph::grapheme_width;
+
+/// Something made of cells that can be drawn on : a [`Console`], a [`crate::ConsoleView`],
+/// a [`crate::Layer`] or your own type.
+///
+/// Only the cell accessors and the size must be implemented. The drawing methods have default
+/// implementations based on them, so that the same drawing code works on every target.
+///
+/// Example
+/// ```
+/// use doryen_rs::{Cell, Color, Console, DrawTarget, TextAlign};
+/// // a target recording the cells written
+/// #[derive(Default)]
+/// struct Recorder(Vec<(i32, i32, Option<u32>)>);
+/// impl DrawTarget for Recorder {
+///     fn get_size(&self) -> (u32, u32) {
+///         (80, 25)
+///     }
+///     fn get_cell(&self, _x: i32, _y: i32) -> Option<Cell> {
+///         None
+///     }
+///     fn put_cell(&mut self, x: i32, y: i32, glyph: Option<u32>, _fore: Option<Color>, _back: Option<Color>) {
+///         self.0.push((x, y, glyph));
+///     }
+/// }
+/// fn draw_status(target: &mut dyn DrawTarget) {
+///     target.print(1, 0, "HP", TextAlign::Left, None, None);
+/// }
+/// let mut recorder = Recorder::default();
+/// draw_status(&mut recorder);
+/// assert_eq!(recorder.0, vec![(1, 0, Some('H' as u32)), (2, 0, Some('P' as u32))]);
+/// let mut con = Console::new(80, 25);
+/// draw_status(&mut con);
+/// assert_eq!(con.get_ascii(2, 0), Some('P' as u16));
+/// ```
+pub trait DrawTarget {
+    /// size in cells
+    fn get_size(&self) -> (u32, u32);
+    /// glyph index, foreground and background colors of a cell, or None if x,y is outside the target
+    fn get_cell(&self, x: i32, y: i32) -> Option<Cell>;
+    /// change the properties of a cell that are not None. Cells outside the target are ignored.
+    fn put_cell(
+        &mut self,
+        x: i32,
+        y: i32,
+        glyph: Option<u32>,
+        fore: Option<Color>,
+        back: Option<Color>,
+    );
+    /// the glyph index used to display a character. Default is the character code point.
+    fn map_char(&self, ch: char) -> u32 {
+        ch as u32
+    }
+    /// a color registered with a name, used by [`DrawTarget::print_color`]
+    fn named_color(&self, _name: &str) -> Option<Color> {
+        None
+    }
+    /// a glyph registered with a name, used by [`DrawTarget::print_color`]
+    fn named_glyph(&self, _name: &str) -> Option<u32> {
+        None
+    }
+    /// write a string. See [`Console::print`]
+    fn print(
+        &mut self,
+        x: i32,
+        y: i32,
+        text: &str,
+        align: TextAlign,
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) {
+        for (cury, line) in (y..).zip(text.split('\n')) {
+            print_line(self, x, cury, line, align, fore, back);
+        }
+    }
+    /// write a string containing color codes. See [`Console::print_color`]
+    fn print_color(&mut self, x: i32, y: i32, text: &str, align: TextAlign, back: Option<Color>) {
+        let mut spans = ColorSpans::default();
+        for (cury, line) in (y..).zip(text.split('\n')) {
+            print_line_color(self, &mut spans, x, cury, line, align, back);
+        }
+    }
+    /// write a string containing color codes inside a rectangle. See [`Console::print_rect`]
+    #[allow(clippy::too_many_arguments)]
+    fn print_rect(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        text: &str,
+        align: TextAlign,
+        back: Option<Color>,
+    ) -> u32 {
+        let lines = Console::wrap_text(w, text);
+        let max_lines = if h == 0 {
+            (self.get_size().1 as i32 - y).max(0) as usize
+        } else {
+            h as usize
+        };
+        let ix = match align {
+            TextAlign::Left => x,
+            TextAlign::Right => x + w as i32 - 1,
+            TextAlign::Center => x + w as i32 / 2,
+        };
+        let mut spans = ColorSpans::default();
+        let mut count = 0;
+        for (cury, line) in (y..).zip(lines.iter().take(max_lines)) {
+            print_line_color(self, &mut spans, ix, cury, line, align, back);
+            count += 1;
+        }
+        count
+    }
+    /// draw a rectangle with the CP437 single line glyphs, possibly filling it with a character.
+    /// See [`Console::rectangle`]
+    #[allow(clippy::too_many_arguments)]
+    fn rectangle(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        fore: Option<Color>,
+        back: Option<Color>,
+        fill: Option<u16>,
+    ) {
+        if w == 0 || h == 0 {
+            return;
+        }
+        let right = x + w as i32 - 1;
+        let down = y + h as i32 - 1;
+        let border = |target: &mut Self, x: i32, y: i32, glyph: u16| {
+            target.put_cell(x, y, Some(u32::from(glyph)), fore, back);
+        };
+        for ix in x + 1..right {
+            border(self, ix, y, CHAR_LINE_H);
+            border(self, ix, down, CHAR_LINE_H);
+        }
+        for iy in y + 1..down {
+            border(self, x, iy, CHAR_LINE_V);
+            border(self, right, iy, CHAR_LINE_V);
+        }
+        border(self, x, y, CHAR_CORNER_NW);
+        border(self, right, y, CHAR_CORNER_NE);
+        border(self, x, down, CHAR_CORNER_SW);
+        border(self, right, down, CHAR_CORNER_SE);
+        if fill.is_some() && w > 2 && h > 2 {
+            self.area(x + 1, y + 1, w - 2, h - 2, fore, back, fill);
+        }
+    }
+    /// fill an area with values
+    #[allow(clippy::too_many_arguments)]
+    fn area(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        fore: Option<Color>,
+        back: Option<Color>,
+        fillchar: Option<u16>,
+    ) {
+        let (width, height) = self.get_size();
+        let fillchar = fillchar.map(u32::from);
+        for iy in y.max(0)..y.saturating_add(h as i32).min(height as i32) {
+            for ix in x.max(0)..x.saturating_add(w as i32).min(width as i32) {
+                self.put_cell(ix, iy, fillchar, fore, back);
+            }
+        }
+    }
+    /// fill the whole target with values
+    fn clear(&mut self, fore: Option<Color>, back: Option<Color>, fillchar: Option<u16>) {
+        let (width, height) = self.get_size();
+        self.area(0, 0, width, height, fore, back, fillchar);
+    }
+}
+
+/// the colors defined by the color codes of a string, see [`DrawTarget::print_color`]
+#[derive(Default)]
+struct ColorSpans {
+    fore: Vec<Color>,
+    back: Vec<Color>,
+}
+
+impl ColorSpans {
+    /// foreground and background colors defined by the current color spans
+    fn colors(&self, back: Option<Color>) -> (Option<Color>, Option<Color>) {
+        let fore = *self.fore.last().unwrap_or(&(255, 255, 255, 255));
+        (Some(fore), self.back.last().copied().or(back))
+    }
+}
+
+/// color defined by a color code : a registered color name or an hexadecimal value
+fn parse_color<T: DrawTarget + ?Sized>(target: &T, code: &str) -> Option<Color> {
+    match code.strip_prefix('#') {
+        Some(hex) => parse_hex_color(hex),
+        None => target.named_color(code),
+    }
+}
+
+fn print_line<T: DrawTarget + ?Sized>(
+    target: &mut T,
+    x: i32,
+    y: i32,
+    text: &str,
+    align: TextAlign,
+    fore: Option<Color>,
+    back: Option<Color>,
+) {
+    let str_len: i32 = text.graphemes(true).map(grapheme_width).sum::<usize>() as i32;
+    let mut ix = match align {
+        TextAlign::Left => x,
+        TextAlign::Right => x - str_len + 1,
+        TextAlign::Center => x - str_len / 2,
+    };
+    let width = target.get_size().0 as i32;
+    for grapheme in text.graphemes(true) {
+        if ix >= width {
+            break;
+        }
+        ix += print_grapheme(target, ix, y, grapheme, fore, back);
+    }
+}
+
+fn print_line_color<T: DrawTarget + ?Sized>(
+    target: &mut T,
+    spans: &mut ColorSpans,
+    x: i32,
+    y: i32,
+    text: &str,
+    align: TextAlign,
+    back: Option<Color>,
+) {
+    let tokens = text_tokens(text);
+    let str_len = tokens
+        .iter()
+        .map(|(token, _)| token.visible_len())
+        .sum::<usize>() as i32;
+    let mut ix = match align {
+        TextAlign::Left => x,
+        TextAlign::Right => x - str_len + 1,
+        TextAlign::Center => x - str_len / 2,
+    };
+    for (token, _) in tokens {
+        match token {
+            TextToken::Fore(code) => match parse_color(target, code) {
+                Some(color) => spans.fore.push(color),
+                None => {
+                    spans.fore.pop();
+                }
+            },
+            TextToken::Back(code) => match parse_color(target, code) {
+                Some(color) => spans.back.push(color),
+                None => {
+                    spans.back.pop();
+                }
+            },
+            TextToken::Grapheme(grapheme) => {
+                let (fore, back) = spans.colors(back);
+                ix += print_grapheme(target, ix, y, grapheme, fore, back);
+            }
+            TextToken::Text(text) => {
+                let (fore, back) = spans.colors(back);
+                for grapheme in text.graphemes(true) {
+                    ix += print_grapheme(target, ix, y, grapheme, fore, back);
+                }
+            }
+            TextToken::Glyph(name) => {
+                let glyph = match name.parse::<u32>() {
+                    Ok(code) => code,
+                    Err(_) => match target.named_glyph(name) {
+                        Some(code) => code,
+                        None => target.map_char('?'),
+                    },
+                };
+                let (fore, back) = spans.colors(back);
+                target.put_cell(ix, y, Some(glyph), fore, back);
+                ix += 1;
+            }
+        }
+    }
+}
+
+/// write a grapheme converted with the target's character mapping and return the number of cells it uses.
+/// Wide characters use two cells, the second one containing a space.
+fn print_grapheme<T: DrawTarget + ?Sized>(
+    target: &mut T,
+    x: i32,
+    y: i32,
+    grapheme: &str,
+    fore: Option<Color>,
+    back: Option<Color>,
+) -> i32 {
+    let width = grapheme_width(grapheme) as i32;
+    if width > 0 {
+        // combining marks can't be displayed in the same cell
+        let ch = grapheme.chars().next().unwrap();
+        let glyph = target.map_char(ch);
+        target.put_cell(x, y, Some(glyph), fore, back);
+        if width == 2 {
+            let space = target.map_char(' ');
+            target.put_cell(x + 1, y, Some(space), fore, back);
+        }
+    }
+    width
+}
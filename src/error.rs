@@ -0,0 +1,53 @@
+use std::fmt;
+
+/// An error happening while loading a file (font, image, xp file...)
+///
+/// Example
+/// ```
+/// use doryen_rs::{FontLoader, LoadError};
+/// let mut loader = FontLoader::new();
+/// loader.load_font("font_axb.png");
+/// assert_eq!(
+///     loader.load_font_async(),
+///     Err(LoadError::InvalidFontName("font_axb.png".to_owned()))
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadError {
+    /// the file could not be opened or read
+    Io { path: String, message: String },
+    /// the character size in the font file name is not valid, like in `font_axb.png`
+    InvalidFontName(String),
+    /// the file content could not be decoded
+    InvalidContent { path: String, message: String },
+}
+
+impl LoadError {
+    /// the path of the file that could not be loaded
+    pub fn path(&self) -> &str {
+        match self {
+            LoadError::Io { path, .. } | LoadError::InvalidContent { path, .. } => path,
+            LoadError::InvalidFontName(path) => path,
+        }
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io { path, message } => {
+                write!(f, "Could not read file {} : {}", path, message)
+            }
+            LoadError::InvalidFontName(path) => write!(
+                f,
+                "Invalid character size in font file name {} : expected name_<width>x<height>.ext",
+                path
+            ),
+            LoadError::InvalidContent { path, message } => {
+                write!(f, "Invalid content in file {} : {}", path, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
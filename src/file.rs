@@ -1,8 +1,18 @@
 use std::collections::HashMap;
-struct AsyncFile(String, uni_app::fs::File, Option<Vec<u8>>);
+
+use crate::error::LoadError;
+use crate::vfs::{with_vfs, Resolved};
+
+/// path, file being read (None for files in memory), content
+struct AsyncFile(
+    String,
+    Option<uni_app::fs::File>,
+    Option<Result<Vec<u8>, LoadError>>,
+);
 
 #[derive(Default)]
-/// This provides a common way to load files for both native and web targets
+/// This provides a common way to load files for both native and web targets.
+/// Paths are resolved through the virtual filesystem, see [`crate::Vfs`].
 pub struct FileLoader {
     files_to_load: HashMap<usize, AsyncFile>,
     seq: usize,
@@ -13,59 +23,76 @@ impl FileLoader {
         Default::default()
     }
     /// request to load a file. returns an id you can use with other methods
-    pub fn load_file(&mut self, path: &str) -> Result<usize, String> {
+    pub fn load_file(&mut self, path: &str) -> Result<usize, LoadError> {
         uni_app::App::print(format!("loading file {}", path));
-        match open_file(path) {
+        let real_path = match with_vfs(|vfs| vfs.resolve(path)) {
+            Resolved::File(real_path) => real_path,
+            Resolved::Memory(buf) => {
+                self.files_to_load
+                    .insert(self.seq, AsyncFile(path.to_owned(), None, Some(Ok(buf))));
+                self.seq += 1;
+                return Ok(self.seq - 1);
+            }
+        };
+        match open_file(&real_path) {
             Ok(mut f) => {
                 if f.is_ready() {
                     match f.read_binary() {
                         Ok(buf) => {
-                            self.files_to_load
-                                .insert(self.seq, AsyncFile(path.to_owned(), f, Some(buf)));
+                            self.files_to_load.insert(
+                                self.seq,
+                                AsyncFile(path.to_owned(), Some(f), Some(Ok(buf))),
+                            );
                             self.seq += 1;
                             Ok(self.seq - 1)
                         }
-                        Err(e) => Err(format!("Could not read file {} : {}\n", path, e)),
+                        Err(e) => Err(LoadError::Io {
+                            path: path.to_owned(),
+                            message: e.to_string(),
+                        }),
                     }
                 } else {
                     uni_app::App::print(format!("loading async file {}", path));
                     self.files_to_load
-                        .insert(self.seq, AsyncFile(path.to_owned(), f, None));
+                        .insert(self.seq, AsyncFile(path.to_owned(), Some(f), None));
                     self.seq += 1;
                     Ok(self.seq - 1)
                 }
             }
-            Err(e) => Err(format!("Could not open file {} : {}\n", path, e)),
+            Err(e) => Err(LoadError::Io {
+                path: path.to_owned(),
+                message: e.to_string(),
+            }),
         }
     }
 
-    fn load_file_async(&mut self) -> bool {
-        for (_, f) in self.files_to_load.iter_mut() {
-            if f.1.is_ready() && f.2.is_none() {
-                match f.1.read_binary() {
-                    Ok(buf) => {
-                        f.2 = Some(buf);
-                    }
-                    Err(e) => panic!("could not load async file {} : {}", f.0, e),
+    fn load_file_async(&mut self) {
+        for (_, AsyncFile(path, file, content)) in self.files_to_load.iter_mut() {
+            if let (Some(file), None) = (file, &content) {
+                if file.is_ready() {
+                    *content = Some(file.read_binary().map_err(|e| LoadError::Io {
+                        path: path.clone(),
+                        message: e.to_string(),
+                    }));
                 }
             }
         }
-        true
     }
 
-    /// return true if the file is ready in memory
-    pub fn check_file_ready(&mut self, id: usize) -> bool {
+    /// return Ok(true) if the file is ready in memory, or the error that happened while reading it
+    pub fn check_file_ready(&mut self, id: usize) -> Result<bool, LoadError> {
         self.load_file_async();
-        if let Some(f) = self.files_to_load.get(&id) {
-            return f.2.is_some();
+        match self.files_to_load.get(&id) {
+            Some(AsyncFile(_, _, Some(Ok(_)))) => Ok(true),
+            Some(AsyncFile(_, _, Some(Err(e)))) => Err(e.clone()),
+            _ => Ok(false),
         }
-        false
     }
 
-    /// retrieve the file content
+    /// retrieve the file content. This must only be called once [`FileLoader::check_file_ready`] returned Ok(true)
     pub fn get_file_content(&mut self, id: usize) -> Vec<u8> {
-        let mut f = self.files_to_load.remove(&id).unwrap();
-        f.2.take().unwrap()
+        let f = self.files_to_load.remove(&id).unwrap();
+        f.2.unwrap().unwrap()
     }
 }
 
@@ -1,62 +1,145 @@
+use crate::error::LoadError;
 use crate::file::FileLoader;
+use crate::glyph::FontLayout;
+#[cfg(feature = "ttf")]
+use crate::ttf::TtfFont;
 
+/// cell size of TrueType/OpenType fonts when it's not in the file name
+const DEFAULT_TTF_CHAR_SIZE: (u32, u32) = (8, 16);
+
+#[derive(Default)]
+/// Loads a font image and prepares it for rendering.
+/// See [`crate::DoryenApi::set_font_path`] for the supported font formats.
 pub struct FontLoader {
     loader: FileLoader,
+    /// the processed font image, once loaded
     pub img: Option<image::RgbaImage>,
+    /// the size of a character in pixels
     pub char_width: u32,
     pub char_height: u32,
     id: usize,
+    path: String,
+    /// number of glyphs per row and column, used when the character size is not in the file name
+    grid_size: (u32, u32),
+    /// whether the file is a TrueType/OpenType font
+    vector: bool,
+    /// error that happened while requesting the file
+    error: Option<LoadError>,
+    /// the rasterized TrueType/OpenType font, once loaded. [`FontLoader::img`] contains its initial atlas.
+    #[cfg(feature = "ttf")]
+    pub ttf: Option<TtfFont>,
 }
 
 impl FontLoader {
     pub fn new() -> Self {
-        Self {
-            loader: FileLoader::new(),
-            img: None,
-            char_width: 0,
-            char_height: 0,
-            id: 0,
-        }
+        Default::default()
     }
+    /// request to load a font with a 16x16 glyphs layout. The character size is parsed from the file name if possible.
     pub fn load_font(&mut self, path: &str) {
-        let start = path.rfind('_').unwrap_or(0);
-        let end = path.rfind('.').unwrap_or(0);
-        if start > 0 && end > 0 {
-            let subpath = path[start + 1..end].to_owned();
-            let charsize: Vec<&str> = subpath.split('x').collect();
-            self.char_width = charsize[0].parse::<u32>().unwrap();
-            self.char_height = charsize[1].parse::<u32>().unwrap();
-        } else {
-            self.char_width = 0;
-            self.char_height = 0;
+        self.load_font_with_layout(path, &FontLayout::Cp437);
+    }
+    /// request to load a font. The character size is parsed from the file name if possible,
+    /// else it's computed from the layout's grid size.
+    ///
+    /// With the `ttf` feature, .ttf and .otf files are rasterized at the character size from the file name
+    /// (for example `DejaVuSansMono_10x20.ttf`), or 8x16 by default. The glyphs are indexed by code point
+    /// in the consoles : use [`FontLayout::Codepoint`].
+    ///
+    /// Errors are returned by [`FontLoader::load_font_async`].
+    pub fn load_font_with_layout(&mut self, path: &str, layout: &FontLayout) {
+        self.grid_size = layout.grid_size();
+        let lower_path = path.to_lowercase();
+        self.vector = lower_path.ends_with(".ttf") || lower_path.ends_with(".otf");
+        self.img = None;
+        self.error = None;
+        self.path = path.to_owned();
+        #[cfg(feature = "ttf")]
+        {
+            self.ttf = None;
+        }
+        match parse_char_size(path) {
+            Ok(Some((char_width, char_height))) => {
+                self.char_width = char_width;
+                self.char_height = char_height;
+            }
+            Ok(None) if self.vector => {
+                (self.char_width, self.char_height) = DEFAULT_TTF_CHAR_SIZE;
+            }
+            Ok(None) => {
+                self.char_width = 0;
+                self.char_height = 0;
+            }
+            Err(e) => {
+                self.error = Some(e);
+                return;
+            }
         }
         match self.loader.load_file(path) {
             Ok(id) => {
                 self.id = id;
-                self.load_font_async();
             }
-            Err(msg)=> {
-                eprint!("Error while loading file {} : {}",path,msg);
+            Err(e) => {
+                self.error = Some(e);
             }
         }
     }
 
-    pub fn load_font_async(&mut self) -> bool {
+    /// return Ok(true) if the font is ready in [`FontLoader::img`],
+    /// or the error that prevented it from being loaded
+    pub fn load_font_async(&mut self) -> Result<bool, LoadError> {
+        if let Some(e) = &self.error {
+            return Err(e.clone());
+        }
         if self.img.is_some() {
-            return true;
+            return Ok(true);
         }
-        if self.loader.check_file_ready(self.id) {
+        if self.loader.check_file_ready(self.id)? {
             let buf = self.loader.get_file_content(self.id);
-            self.load_font_bytes(&buf);
-            return true;
+            if let Err(e) = self.load_font_bytes(&buf) {
+                self.error = Some(e.clone());
+                return Err(e);
+            }
+            return Ok(true);
         }
-        false
+        Ok(false)
     }
 
-    fn load_font_bytes(&mut self, buf: &[u8]) {
-        let mut img = image::load_from_memory(buf).unwrap().to_rgba8();
+    fn load_font_bytes(&mut self, buf: &[u8]) -> Result<(), LoadError> {
+        if self.vector {
+            return self.load_ttf_bytes(buf);
+        }
+        let mut img = image::load_from_memory(buf)
+            .map_err(|e| self.content_error(e.to_string()))?
+            .to_rgba8();
         self.process_image(&mut img);
+        if self.char_width == 0 {
+            let (columns, rows) = self.grid_size;
+            self.char_width = img.width() / columns.max(1);
+            self.char_height = img.height() / rows.max(1);
+        }
         self.img = Some(img);
+        Ok(())
+    }
+
+    #[cfg(feature = "ttf")]
+    fn load_ttf_bytes(&mut self, buf: &[u8]) -> Result<(), LoadError> {
+        let ttf = TtfFont::from_bytes(buf.to_vec(), self.char_width, self.char_height)
+            .map_err(|e| self.content_error(e))?;
+        self.img = Some(ttf.atlas().clone());
+        self.ttf = Some(ttf);
+        Ok(())
+    }
+
+    #[cfg(not(feature = "ttf"))]
+    fn load_ttf_bytes(&mut self, _buf: &[u8]) -> Result<(), LoadError> {
+        Err(self.content_error("TrueType/OpenType fonts require the ttf feature".to_owned()))
+    }
+
+    fn content_error(&self, message: String) -> LoadError {
+        LoadError::InvalidContent {
+            path: self.path.clone(),
+            message,
+        }
     }
 
     fn process_image(&mut self, img: &mut image::RgbaImage) {
@@ -91,3 +174,23 @@ impl FontLoader {
         }
     }
 }
+
+/// parse the character size at the end of a font file name, like `terminal_8x8.png`.
+/// Returns None if the name doesn't contain a size.
+fn parse_char_size(path: &str) -> Result<Option<(u32, u32)>, LoadError> {
+    let start = path.rfind('_').unwrap_or(0);
+    let end = path.rfind('.').unwrap_or(0);
+    if start == 0 || end <= start {
+        return Ok(None);
+    }
+    let mut size = path[start + 1..end].split('x');
+    match (size.next(), size.next(), size.next()) {
+        (Some(width), Some(height), None) if !width.is_empty() && !height.is_empty() => {
+            match (width.parse::<u32>(), height.parse::<u32>()) {
+                (Ok(width), Ok(height)) if width > 0 && height > 0 => Ok(Some((width, height))),
+                _ => Err(LoadError::InvalidFontName(path.to_owned())),
+            }
+        }
+        _ => Ok(None),
+    }
+}
@@ -0,0 +1,210 @@
+use crate::console::TextAlign;
+use crate::cp437::{char_to_cp437, CP437_TO_UNICODE};
+use crate::glyph::GlyphMap;
+
+/// The glyphs used to draw the border of a frame with [`crate::Console::frame`], and its optional title.
+///
+/// The CP437 styles contain glyph indices in the CP437 layout, like [`crate::Console::rectangle`].
+/// The unicode styles contain code points : use them as is with fonts ordered by code point,
+/// or convert them with [`FrameStyle::map`] for other layouts.
+///
+/// Example
+/// ```
+/// use doryen_rs::{Console, FrameStyle, TextAlign};
+/// let mut con = Console::new(20, 10);
+/// let style = FrameStyle::DOUBLE.with_title("Inventory", TextAlign::Center);
+/// con.frame(0, 0, 20, 10, &style, None, None, None);
+/// assert_eq!(con.get_glyph(0, 0), Some(201)); // ╔
+/// assert_eq!(con.get_glyph(6, 0), Some('I' as u32));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameStyle {
+    /// the border glyphs : top-left, top, top-right, left, right, bottom-left, bottom, bottom-right
+    pub glyphs: [u32; 8],
+    /// whether the glyphs are CP437 indices or unicode code points. Used to compute the junctions
+    pub cp437: bool,
+    /// when a border is drawn over another one, replace the glyph by a junction (like ┼ or ├)
+    pub junctions: bool,
+    /// text printed in the top border
+    pub title: Option<String>,
+    pub title_align: TextAlign,
+}
+
+impl FrameStyle {
+    /// single lines ┌─┐
+    pub const SINGLE: FrameStyle = FrameStyle::cp437([218, 196, 191, 179, 179, 192, 196, 217]);
+    /// double lines ╔═╗
+    pub const DOUBLE: FrameStyle = FrameStyle::cp437([201, 205, 187, 186, 186, 200, 205, 188]);
+    /// double horizontal lines, single vertical lines ╒═╕
+    pub const DOUBLE_HORIZONTAL: FrameStyle =
+        FrameStyle::cp437([213, 205, 184, 179, 179, 212, 205, 190]);
+    /// single horizontal lines, double vertical lines ╓─╖
+    pub const DOUBLE_VERTICAL: FrameStyle =
+        FrameStyle::cp437([214, 196, 183, 186, 186, 211, 196, 189]);
+    /// unicode single lines ┌─┐
+    pub const UNICODE_SINGLE: FrameStyle =
+        FrameStyle::unicode(['┌', '─', '┐', '│', '│', '└', '─', '┘']);
+    /// unicode double lines ╔═╗
+    pub const UNICODE_DOUBLE: FrameStyle =
+        FrameStyle::unicode(['╔', '═', '╗', '║', '║', '╚', '═', '╝']);
+    /// unicode heavy lines ┏━┓
+    pub const UNICODE_HEAVY: FrameStyle =
+        FrameStyle::unicode(['┏', '━', '┓', '┃', '┃', '┗', '━', '┛']);
+    /// unicode single lines with rounded corners ╭─╮
+    pub const UNICODE_ROUNDED: FrameStyle =
+        FrameStyle::unicode(['╭', '─', '╮', '│', '│', '╰', '─', '╯']);
+
+    const fn cp437(glyphs: [u32; 8]) -> Self {
+        Self {
+            glyphs,
+            cp437: true,
+            junctions: true,
+            title: None,
+            title_align: TextAlign::Left,
+        }
+    }
+    const fn unicode(chars: [char; 8]) -> Self {
+        let mut glyphs = [0; 8];
+        let mut i = 0;
+        while i < 8 {
+            glyphs[i] = chars[i] as u32;
+            i += 1;
+        }
+        Self {
+            glyphs,
+            cp437: false,
+            junctions: true,
+            title: None,
+            title_align: TextAlign::Left,
+        }
+    }
+    /// a custom style with the border characters in this order :
+    /// top-left, top, top-right, left, right, bottom-left, bottom, bottom-right.
+    /// The characters are converted into glyph indices with the glyph map. Junctions are disabled.
+    pub fn custom(chars: [char; 8], map: &GlyphMap) -> Self {
+        Self::unicode(chars).map(map)
+    }
+    /// convert the code points of a unicode style into glyph indices.
+    /// Junctions are disabled since the glyph indices are specific to the font.
+    pub fn map(mut self, map: &GlyphMap) -> Self {
+        for glyph in self.glyphs.iter_mut() {
+            *glyph = map.get(char::from_u32(*glyph).unwrap_or(' '));
+        }
+        self.junctions = false;
+        self
+    }
+    pub fn with_title(mut self, title: &str, align: TextAlign) -> Self {
+        self.title = Some(title.to_owned());
+        self.title_align = align;
+        self
+    }
+    pub fn with_junctions(mut self, junctions: bool) -> Self {
+        self.junctions = junctions;
+        self
+    }
+    /// the glyph to draw over `existing` to connect the two borders.
+    /// Returns `glyph` if `existing` is not a box drawing glyph or if no junction glyph exists.
+    pub(crate) fn junction(&self, existing: u32, glyph: u32) -> u32 {
+        if !self.junctions || existing == glyph {
+            return glyph;
+        }
+        let (existing_arms, glyph_arms) = match (self.arms(existing), self.arms(glyph)) {
+            (Some(existing_arms), Some(glyph_arms)) => (existing_arms, glyph_arms),
+            _ => return glyph,
+        };
+        let mut arms = glyph_arms;
+        for (arm, existing) in arms.iter_mut().zip(existing_arms) {
+            if *arm == NONE {
+                *arm = existing;
+            }
+        }
+        if let Some(junction) = self.encode(arms) {
+            return junction;
+        }
+        // no glyph mixes these line weights : use the new border's weight everywhere
+        let weight = glyph_arms
+            .iter()
+            .copied()
+            .find(|&arm| arm != NONE)
+            .unwrap_or(LIGHT);
+        for arm in arms.iter_mut() {
+            if *arm != NONE {
+                *arm = weight;
+            }
+        }
+        self.encode(arms).unwrap_or(glyph)
+    }
+    fn arms(&self, glyph: u32) -> Option<[u8; 4]> {
+        let ch = if self.cp437 {
+            *CP437_TO_UNICODE.get(glyph as usize)?
+        } else {
+            char::from_u32(glyph)?
+        };
+        BOX_CHARS
+            .iter()
+            .chain(ROUNDED_CHARS.iter())
+            .find(|(c, _)| *c == ch)
+            .map(|(_, arms)| *arms)
+    }
+    fn encode(&self, arms: [u8; 4]) -> Option<u32> {
+        let ch = BOX_CHARS.iter().find(|(_, a)| *a == arms)?.0;
+        if self.cp437 {
+            char_to_cp437(ch).map(u32::from)
+        } else {
+            Some(ch as u32)
+        }
+    }
+}
+
+impl Default for FrameStyle {
+    fn default() -> Self {
+        Self::SINGLE
+    }
+}
+
+const NONE: u8 = 0;
+const LIGHT: u8 = 1;
+const HEAVY: u8 = 2;
+const DOUBLE: u8 = 3;
+
+/// the lines of box drawing characters : north, east, south, west
+#[rustfmt::skip]
+const BOX_CHARS: [(char, [u8; 4]); 55] = [
+    ('─', [NONE, LIGHT, NONE, LIGHT]), ('│', [LIGHT, NONE, LIGHT, NONE]),
+    ('┌', [NONE, LIGHT, LIGHT, NONE]), ('┐', [NONE, NONE, LIGHT, LIGHT]),
+    ('└', [LIGHT, LIGHT, NONE, NONE]), ('┘', [LIGHT, NONE, NONE, LIGHT]),
+    ('├', [LIGHT, LIGHT, LIGHT, NONE]), ('┤', [LIGHT, NONE, LIGHT, LIGHT]),
+    ('┬', [NONE, LIGHT, LIGHT, LIGHT]), ('┴', [LIGHT, LIGHT, NONE, LIGHT]),
+    ('┼', [LIGHT, LIGHT, LIGHT, LIGHT]),
+    ('━', [NONE, HEAVY, NONE, HEAVY]), ('┃', [HEAVY, NONE, HEAVY, NONE]),
+    ('┏', [NONE, HEAVY, HEAVY, NONE]), ('┓', [NONE, NONE, HEAVY, HEAVY]),
+    ('┗', [HEAVY, HEAVY, NONE, NONE]), ('┛', [HEAVY, NONE, NONE, HEAVY]),
+    ('┣', [HEAVY, HEAVY, HEAVY, NONE]), ('┫', [HEAVY, NONE, HEAVY, HEAVY]),
+    ('┳', [NONE, HEAVY, HEAVY, HEAVY]), ('┻', [HEAVY, HEAVY, NONE, HEAVY]),
+    ('╋', [HEAVY, HEAVY, HEAVY, HEAVY]),
+    ('═', [NONE, DOUBLE, NONE, DOUBLE]), ('║', [DOUBLE, NONE, DOUBLE, NONE]),
+    ('╔', [NONE, DOUBLE, DOUBLE, NONE]), ('╗', [NONE, NONE, DOUBLE, DOUBLE]),
+    ('╚', [DOUBLE, DOUBLE, NONE, NONE]), ('╝', [DOUBLE, NONE, NONE, DOUBLE]),
+    ('╠', [DOUBLE, DOUBLE, DOUBLE, NONE]), ('╣', [DOUBLE, NONE, DOUBLE, DOUBLE]),
+    ('╦', [NONE, DOUBLE, DOUBLE, DOUBLE]), ('╩', [DOUBLE, DOUBLE, NONE, DOUBLE]),
+    ('╬', [DOUBLE, DOUBLE, DOUBLE, DOUBLE]),
+    ('╒', [NONE, DOUBLE, LIGHT, NONE]), ('╓', [NONE, LIGHT, DOUBLE, NONE]),
+    ('╕', [NONE, NONE, LIGHT, DOUBLE]), ('╖', [NONE, NONE, DOUBLE, LIGHT]),
+    ('╘', [LIGHT, DOUBLE, NONE, NONE]), ('╙', [DOUBLE, LIGHT, NONE, NONE]),
+    ('╛', [LIGHT, NONE, NONE, DOUBLE]), ('╜', [DOUBLE, NONE, NONE, LIGHT]),
+    ('╞', [LIGHT, DOUBLE, LIGHT, NONE]), ('╟', [DOUBLE, LIGHT, DOUBLE, NONE]),
+    ('╡', [LIGHT, NONE, LIGHT, DOUBLE]), ('╢', [DOUBLE, NONE, DOUBLE, LIGHT]),
+    ('╤', [NONE, DOUBLE, LIGHT, DOUBLE]), ('╥', [NONE, LIGHT, DOUBLE, LIGHT]),
+    ('╧', [LIGHT, DOUBLE, NONE, DOUBLE]), ('╨', [DOUBLE, LIGHT, NONE, LIGHT]),
+    ('╪', [LIGHT, DOUBLE, LIGHT, DOUBLE]), ('╫', [DOUBLE, LIGHT, DOUBLE, LIGHT]),
+    ('╴', [NONE, NONE, NONE, LIGHT]), ('╵', [LIGHT, NONE, NONE, NONE]),
+    ('╶', [NONE, LIGHT, NONE, NONE]), ('╷', [NONE, NONE, LIGHT, NONE]),
+];
+
+/// rounded corners are only used as input : junctions use square corners
+const ROUNDED_CHARS: [(char, [u8; 4]); 4] = [
+    ('╭', [NONE, LIGHT, LIGHT, NONE]),
+    ('╮', [NONE, NONE, LIGHT, LIGHT]),
+    ('╯', [LIGHT, NONE, NONE, LIGHT]),
+    ('╰', [LIGHT, LIGHT, NONE, NONE]),
+];
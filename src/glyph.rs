@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::iter::FromIterator;
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::cp437::CP437_TO_UNICODE;
+
+/// Converts the unicode characters printed on a console into glyph indices in the font.
+///
+/// An empty map converts each character into its code point, which works with fonts
+/// where the glyphs are ordered by code point (like the `unicode_16x16.png` font from the unicode example).
+/// Characters missing from a non empty map are converted into the fallback glyph if defined, or into their code point.
+///
+/// Example
+/// ```
+/// use doryen_rs::{Console, GlyphMap, TextAlign};
+/// let mut map = GlyphMap::new();
+/// map.insert('♥', 3);
+/// let mut con = Console::new(10, 1);
+/// con.set_glyph_map(map);
+/// con.print(0, 0, "♥A", TextAlign::Left, None, None);
+/// assert_eq!(con.get_glyph(0, 0), Some(3));
+/// assert_eq!(con.get_glyph(1, 0), Some('A' as u32));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct GlyphMap {
+    glyphs: HashMap<u32, u32>,
+    fallback: Option<u32>,
+}
+
+impl GlyphMap {
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// associate a character with a glyph index
+    pub fn insert(&mut self, ch: char, glyph: u32) {
+        self.glyphs.insert(ch as u32, glyph);
+    }
+    /// define the glyph used for characters that are not in the map.
+    /// With None, these characters use their code point as glyph index.
+    pub fn set_fallback(&mut self, glyph: Option<u32>) {
+        self.fallback = glyph;
+    }
+    /// return the glyph index for a character
+    pub fn get(&self, ch: char) -> u32 {
+        match self.glyphs.get(&(ch as u32)) {
+            Some(&glyph) => glyph,
+            None => self.fallback.unwrap_or(ch as u32),
+        }
+    }
+    /// whether a character has been associated with a glyph
+    pub fn contains(&self, ch: char) -> bool {
+        self.glyphs.contains_key(&(ch as u32))
+    }
+    /// Parse a glyph map file. Each line contains a character and its glyph index separated by spaces.
+    /// The character is either written as is or as an hexadecimal code point `U+XXXX`.
+    /// Empty lines and lines starting with `//` are ignored.
+    ///
+    /// Example
+    /// ```
+    /// use doryen_rs::GlyphMap;
+    /// let map = GlyphMap::parse("// my font\n@ 0\nU+00E9 1\n").unwrap();
+    /// assert_eq!(map.get('@'), 0);
+    /// assert_eq!(map.get('é'), 1);
+    /// ```
+    pub fn parse(content: &str) -> Result<GlyphMap, String> {
+        let mut map = GlyphMap::new();
+        for (num, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+            let error = || format!("Invalid glyph map line {} : {}", num + 1, line);
+            let mut fields = line.split_whitespace();
+            let (ch, glyph) = match (fields.next(), fields.next(), fields.next()) {
+                (Some(ch), Some(glyph), None) => (ch, glyph),
+                _ => return Err(error()),
+            };
+            let ch = match ch.strip_prefix("U+") {
+                Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+                None => {
+                    let mut chars = ch.chars();
+                    chars.next().filter(|_| chars.next().is_none())
+                }
+            }
+            .ok_or_else(error)?;
+            map.insert(ch, glyph.parse().map_err(|_| error())?);
+        }
+        Ok(map)
+    }
+    pub fn len(&self) -> usize {
+        self.glyphs.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.glyphs.is_empty()
+    }
+}
+
+impl FromIterator<(char, u32)> for GlyphMap {
+    fn from_iter<I: IntoIterator<Item = (char, u32)>>(iter: I) -> Self {
+        let mut map = GlyphMap::new();
+        for (ch, glyph) in iter {
+            map.insert(ch, glyph);
+        }
+        map
+    }
+}
+
+/// number of console cells used to display a grapheme :
+/// 0 for isolated combining marks, 2 for East Asian wide characters, 1 otherwise.
+pub(crate) fn grapheme_width(grapheme: &str) -> usize {
+    grapheme.width().min(2)
+}
+
+/// How the glyphs are organized in a font image.
+/// The glyph index of a character is its position in the font image, from left to right, then top to bottom.
+#[derive(Clone, Debug, Default)]
+pub enum FontLayout {
+    /// glyphs are ordered by unicode code point (like the `unicode_16x16.png` font)
+    #[default]
+    Codepoint,
+    /// the 256 CP437 glyphs on 16 rows of 16 glyphs (libtcod's `ASCII_INROW` layout).
+    /// This is the layout of most roguelike fonts, including the default `terminal_8x8.png` font.
+    Cp437,
+    /// the 256 CP437 glyphs on 16 columns of 16 glyphs (libtcod's `ASCII_INCOL` layout)
+    Cp437ColumnMajor,
+    /// libtcod's own layout on 8 rows of 32 glyphs (libtcod's `TCOD` layout)
+    Tcod,
+    /// a custom mapping, for example loaded with [`GlyphMap::parse`]
+    Custom(GlyphMap),
+}
+
+/// position of the CP437 characters in the `TCOD` layout. 0 = not in the font (except for the space)
+const TCOD_LAYOUT: [u32; 256] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 76, 77, 0, 0, 0, 0, 0, //
+    71, 70, 72, 0, 0, 0, 0, 0, 64, 65, 67, 66, 0, 73, 68, 69, //
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, //
+    16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, //
+    32, 96, 97, 98, 99, 100, 101, 102, 103, 104, 105, 106, 107, 108, 109, 110, //
+    111, 112, 113, 114, 115, 116, 117, 118, 119, 120, 121, 33, 34, 35, 36, 37, //
+    38, 128, 129, 130, 131, 132, 133, 134, 135, 136, 137, 138, 139, 140, 141, 142, //
+    143, 144, 145, 146, 147, 148, 149, 150, 151, 152, 153, 39, 40, 41, 42, 0, //
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, //
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, //
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, //
+    43, 44, 45, 46, 49, 0, 0, 0, 0, 81, 78, 87, 88, 0, 0, 55, //
+    53, 50, 52, 51, 47, 48, 0, 0, 85, 86, 82, 84, 83, 79, 80, 0, //
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 56, 54, 0, 0, 0, 0, 0, //
+    74, 75, 57, 58, 59, 60, 61, 62, 63, 0, 0, 0, 0, 0, 0, 0, //
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+];
+
+impl FontLayout {
+    /// the glyph map converting characters into glyph indices for this layout.
+    /// Characters missing from CP437 layouts are displayed as '?'.
+    ///
+    /// Example
+    /// ```
+    /// use doryen_rs::FontLayout;
+    /// let map = FontLayout::Cp437.glyph_map();
+    /// assert_eq!(map.get('A'), 65);
+    /// assert_eq!(map.get('é'), 130);
+    /// assert_eq!(map.get('┼'), 197);
+    /// assert_eq!(FontLayout::Cp437ColumnMajor.glyph_map().get('A'), 20);
+    /// assert_eq!(FontLayout::Tcod.glyph_map().get('A'), 96);
+    /// ```
+    pub fn glyph_map(&self) -> GlyphMap {
+        let mut map = GlyphMap::new();
+        match self {
+            FontLayout::Codepoint => (),
+            FontLayout::Cp437 => {
+                for (code, &ch) in CP437_TO_UNICODE.iter().enumerate().skip(1) {
+                    map.insert(ch, code as u32);
+                }
+                map.set_fallback(Some('?' as u32));
+            }
+            FontLayout::Cp437ColumnMajor => {
+                for (code, &ch) in CP437_TO_UNICODE.iter().enumerate().skip(1) {
+                    map.insert(ch, (code as u32 % 16) * 16 + code as u32 / 16);
+                }
+                map.set_fallback(Some(('?' as u32 % 16) * 16 + '?' as u32 / 16));
+            }
+            FontLayout::Tcod => {
+                for (code, &ch) in CP437_TO_UNICODE.iter().enumerate() {
+                    if TCOD_LAYOUT[code] > 0 {
+                        map.insert(ch, TCOD_LAYOUT[code]);
+                    }
+                }
+                map.insert(' ', 0);
+                map.set_fallback(Some(TCOD_LAYOUT['?' as usize]));
+            }
+            FontLayout::Custom(custom) => map = custom.clone(),
+        }
+        map
+    }
+    /// number of glyphs per row and per column in the font image.
+    /// Used to compute the character size when it's not in the font file name.
+    pub fn grid_size(&self) -> (u32, u32) {
+        match self {
+            FontLayout::Tcod => (32, 8),
+            _ => (16, 16),
+        }
+    }
+}
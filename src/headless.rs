@@ -0,0 +1,226 @@
+use uni_app::AppEvent;
+
+use crate::app::{
+    reload_assets, update_ticks, AppOptions, DoryenApi, DoryenApiImpl, Engine, UpdateEvent,
+    SKIP_TICKS,
+};
+use crate::console::Console;
+use crate::font::FontLoader;
+use crate::input::DoryenInput;
+use crate::renderer::SoftwareRenderer;
+
+/// Runs an [`Engine`] without opening a window nor creating a GL context.
+///
+/// This uses the same fixed tick loop as [`App::run`](crate::App::run) but time only flows when you
+/// call [`HeadlessApp::step`] or [`HeadlessApp::run_ticks`], and the player input comes from
+/// events you provide with [`HeadlessApp::send_event`] or [`HeadlessApp::schedule_event`].
+/// The engine's render function is called after each step and the resulting root console
+/// can be checked with [`HeadlessApp::con`].
+///
+/// Mouse positions are in pixels, the screen size being the one defined in [`AppOptions`].
+///
+/// The font is loaded but only used to render screenshots with [`UpdateEvent::Capture`]
+/// or [`DoryenApi::capture_screen`].
+///
+/// Example
+/// ```
+/// use doryen_rs::{AppOptions, DoryenApi, Engine, HeadlessApp, TextAlign, UpdateEvent};
+/// struct Counter(u32);
+/// impl Engine for Counter {
+///     fn update(&mut self, _api: &mut dyn DoryenApi) -> Option<UpdateEvent> {
+///         self.0 += 1;
+///         if self.0 == 10 { Some(UpdateEvent::Exit) } else { None }
+///     }
+///     fn render(&mut self, api: &mut dyn DoryenApi) {
+///         api.con().print(0, 0, &self.0.to_string(), TextAlign::Left, None, None);
+///     }
+/// }
+/// let mut app = HeadlessApp::new(AppOptions::default());
+/// app.set_engine(Box::new(Counter(0)));
+/// assert_eq!(app.run_ticks(100), 10);
+/// assert!(app.has_exited());
+/// assert_eq!(app.con().get_ascii(0, 0), Some('1' as u16));
+/// ```
+pub struct HeadlessApp {
+    api: DoryenApiImpl,
+    engine: Option<Box<dyn Engine>>,
+    font_loader: FontLoader,
+    font_loading: bool,
+    /// additional fonts being loaded
+    extra_fonts: Vec<(usize, FontLoader)>,
+    time: f64,
+    next_tick: f64,
+    ticks: u64,
+    initialized: bool,
+    exited: bool,
+    events: Vec<(u64, AppEvent)>,
+}
+
+impl HeadlessApp {
+    pub fn new(options: AppOptions) -> Self {
+        let input = DoryenInput::new(
+            (options.screen_width, options.screen_height),
+            (options.console_width, options.console_height),
+            (0, 0),
+        );
+        let mut api = DoryenApiImpl::new(&options, input);
+        api.set_font_path(&options.font_path);
+        Self {
+            api,
+            engine: None,
+            font_loader: FontLoader::new(),
+            font_loading: false,
+            extra_fonts: Vec::new(),
+            time: 0.0,
+            next_tick: 0.0,
+            ticks: 0,
+            initialized: false,
+            exited: false,
+            events: Vec::new(),
+        }
+    }
+    pub fn set_engine(&mut self, engine: Box<dyn Engine>) {
+        self.engine = Some(engine);
+        self.initialized = false;
+    }
+    /// send an input event that will be processed before the next update
+    pub fn send_event(&mut self, event: AppEvent) {
+        self.events.push((self.ticks, event));
+    }
+    /// send an input event that will be processed before the update of the given tick (starting at 0)
+    pub fn schedule_event(&mut self, tick: u64, event: AppEvent) {
+        self.events.push((tick, event));
+    }
+    /// advance the clock by `elapsed` seconds, run all the updates that should have happened during
+    /// this time, then render the root console.
+    /// Returns false if the engine has returned [`UpdateEvent::Exit`].
+    pub fn step(&mut self, elapsed: f64) -> bool {
+        if self.exited {
+            return false;
+        }
+        let mut engine = match self.engine.take() {
+            Some(engine) => engine,
+            None => return false,
+        };
+        if !self.initialized {
+            engine.init(&mut self.api);
+            self.initialized = true;
+        }
+        self.time += elapsed;
+        reload_assets(&mut *engine, &mut self.api, self.time);
+        self.load_font();
+        let (ticks, events) = (&mut self.ticks, &mut self.events);
+        let mut before_update = |api: &mut DoryenApiImpl| {
+            dispatch_events(events, *ticks, &mut api.input);
+            *ticks += 1;
+        };
+        for event in update_ticks(
+            &mut *engine,
+            &mut self.api,
+            self.time,
+            &mut self.next_tick,
+            &mut before_update,
+        ) {
+            match event {
+                UpdateEvent::Capture(filepath) => self.api.save_capture(&filepath),
+                UpdateEvent::Exit => self.exited = true,
+            }
+        }
+        engine.render(&mut self.api);
+        self.api.compose_frame();
+        self.engine = Some(engine);
+        !self.exited
+    }
+    /// run up to `count` ticks, rendering after each one.
+    /// Returns the number of ticks actually run, which is lower than `count` if the engine exits.
+    pub fn run_ticks(&mut self, count: u64) -> u64 {
+        let start = self.ticks;
+        for _ in 0..count {
+            if !self.step(SKIP_TICKS) {
+                break;
+            }
+        }
+        self.ticks - start
+    }
+    /// the root console, as rendered by the last step
+    pub fn con(&self) -> &Console {
+        &self.api.con
+    }
+    /// the root console with the layers drawn over it, as displayed by the last step
+    pub fn frame(&self) -> &Console {
+        self.api.frame()
+    }
+    /// the API given to the engine, to setup the game state between steps
+    pub fn api(&mut self) -> &mut dyn DoryenApi {
+        &mut self.api
+    }
+    /// whether the engine has returned [`UpdateEvent::Exit`]
+    pub fn has_exited(&self) -> bool {
+        self.exited
+    }
+    /// number of update ticks run since the start
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+    /// current value of the manual clock in seconds
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+    fn load_font(&mut self) {
+        if let Some(font_path) = self.api.font_path.take() {
+            self.font_loader
+                .load_font_with_layout(&font_path, &self.api.font_layout);
+            self.font_loading = true;
+        }
+        if self.font_loading {
+            match self.font_loader.load_font_async() {
+                Ok(true) => {
+                    self.api.renderer = SoftwareRenderer::from_font_loader(&self.font_loader);
+                    self.font_loader.img = None;
+                    #[cfg(feature = "ttf")]
+                    {
+                        self.api.ttf = self.font_loader.ttf.take();
+                    }
+                    self.font_loading = false;
+                }
+                Ok(false) => (),
+                Err(e) => {
+                    self.api.font_errors.insert(0, e);
+                    self.font_loading = false;
+                }
+            }
+        }
+        for (id, font_path, layout) in self.api.new_fonts.drain(..) {
+            let mut loader = FontLoader::new();
+            loader.load_font_with_layout(&font_path, &layout);
+            self.extra_fonts.push((id, loader));
+        }
+        let api = &mut self.api;
+        self.extra_fonts
+            .retain_mut(|(id, loader)| match loader.load_font_async() {
+                Ok(true) => {
+                    if let Some(renderer) = SoftwareRenderer::from_font_loader(loader) {
+                        api.font_renderers.insert(*id, renderer);
+                    }
+                    false
+                }
+                Ok(false) => true,
+                Err(e) => {
+                    api.font_errors.insert(*id, e);
+                    false
+                }
+            });
+    }
+}
+
+/// send to the input the events scheduled before or at the given tick
+fn dispatch_events(events: &mut Vec<(u64, AppEvent)>, tick: u64, input: &mut DoryenInput) {
+    events.retain(|(event_tick, event)| {
+        if *event_tick <= tick {
+            input.on_event(event);
+            false
+        } else {
+            true
+        }
+    });
+}
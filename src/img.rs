@@ -1,12 +1,22 @@
 #![warn(clippy::float_cmp)]
 use crate::color::{color_blend, color_dist, Color};
 use crate::console::*;
+use crate::error::LoadError;
 use crate::file::FileLoader;
+use crate::watch::{file_version, unwatch_file, watch_file};
 
 /// An easy way to load PNG images and blit them on the console
 pub struct Image {
     file_loader: FileLoader,
     img: Option<image::RgbaImage>,
+    path: String,
+    /// id of the file request in the file loader
+    file_id: usize,
+    error: Option<LoadError>,
+    /// version of the file when it was loaded, to detect hot reloading
+    version: u64,
+    /// whether the file is watched for hot reloading
+    watched: bool,
 }
 
 impl Image {
@@ -15,12 +25,25 @@ impl Image {
     /// Using blit methods before the image is loaded has no impact on the console.
     pub fn new(file_path: &str) -> Self {
         let mut file_loader = FileLoader::new();
-        file_loader.load_file(file_path).ok();
+        let (file_id, error) = match file_loader.load_file(file_path) {
+            Ok(file_id) => (file_id, None),
+            Err(e) => (0, Some(e)),
+        };
+        let watched = watch_file(file_path);
         Self {
             file_loader,
             img: None,
+            path: file_path.to_owned(),
+            file_id,
+            error,
+            version: file_version(file_path),
+            watched,
         }
     }
+    /// the error that prevented the image from being loaded, if any
+    pub fn error(&self) -> Option<&LoadError> {
+        self.error.as_ref()
+    }
     /// Returns the image's width in pixels or 0 if the image has not yet been loaded
     pub fn width(&self) -> u32 {
         if let Some(ref img) = self.img {
@@ -40,6 +63,11 @@ impl Image {
         Self {
             file_loader: FileLoader::new(),
             img: Some(image::RgbaImage::new(width, height)),
+            path: String::new(),
+            file_id: 0,
+            error: None,
+            version: 0,
+            watched: false,
         }
     }
     /// get the color of a specific pixel inside the image
@@ -59,19 +87,51 @@ impl Image {
     /// Check if the image has been loaded.
     /// Since there's no background thread doing the work for you, you have to call some method on image for it to actually load.
     /// Use either [`Image::try_load`], [`Image::get_size`], [`Image::blit`] or [`Image::blit_ex`] to run the loading code.
+    /// Returns false if the image could not be loaded, see [`Image::error`].
     pub fn try_load(&mut self) -> bool {
+        if !self.path.is_empty() {
+            let version = file_version(&self.path);
+            if version != self.version {
+                self.version = version;
+                self.img = None;
+                match self.file_loader.load_file(&self.path) {
+                    Ok(file_id) => {
+                        self.file_id = file_id;
+                        self.error = None;
+                    }
+                    Err(e) => self.error = Some(e),
+                }
+            }
+        }
         if self.img.is_some() {
             return true;
         }
-        if self.file_loader.check_file_ready(0) {
-            let buf = self.file_loader.get_file_content(0);
-            self.intialize_image(&buf);
-            return true;
+        if self.error.is_some() {
+            return false;
+        }
+        match self.file_loader.check_file_ready(self.file_id) {
+            Ok(true) => {
+                let buf = self.file_loader.get_file_content(self.file_id);
+                self.intialize_image(&buf);
+                self.img.is_some()
+            }
+            Ok(false) => false,
+            Err(e) => {
+                self.error = Some(e);
+                false
+            }
         }
-        false
     }
     fn intialize_image(&mut self, buf: &[u8]) {
-        self.img = Some(image::load_from_memory(buf).unwrap().to_rgba8());
+        match image::load_from_memory(buf) {
+            Ok(img) => self.img = Some(img.to_rgba8()),
+            Err(e) => {
+                self.error = Some(LoadError::InvalidContent {
+                    path: self.path.clone(),
+                    message: e.to_string(),
+                })
+            }
+        }
     }
     /// If the image has already been loaded, return its size, else return None
     pub fn try_get_size(&mut self) -> Option<(u32, u32)> {
@@ -101,7 +161,13 @@ impl Image {
             let offx = if x < 0 { -x } else { 0 };
             let offy = if y < 0 { -y } else { 0 };
             let con_width = con.get_pot_width();
-            let back = con.borrow_mut_background();
+            con.mark_dirty(
+                minx,
+                miny,
+                (maxx - minx).max(0) as u32,
+                (maxy - miny).max(0) as u32,
+            );
+            let (_, _, back) = con.buffers_mut();
             for cx in minx..maxx {
                 for cy in miny..maxy {
                     let pixel = img.get_pixel((cx - minx + offx) as u32, (cy - miny + offy) as u32);
@@ -177,7 +243,13 @@ impl Image {
         let invscalex = 1.0 / scalex;
         let invscaley = 1.0 / scaley;
         let con_width = con.get_pot_width();
-        let back = con.borrow_mut_background();
+        con.mark_dirty(
+            minx,
+            miny,
+            (maxx - minx).max(0) as u32,
+            (maxy - miny).max(0) as u32,
+        );
+        let (_, _, back) = con.buffers_mut();
         if let Some(ref img) = self.img {
             for cx in minx..maxx {
                 for cy in miny..maxy {
@@ -351,6 +423,14 @@ impl Image {
     }
 }
 
+impl Drop for Image {
+    fn drop(&mut self) {
+        if self.watched {
+            unwatch_file(&self.path);
+        }
+    }
+}
+
 const FLAG_TO_ASCII: [i32; 8] = [
     0,
     CHAR_SUBP_NE as i32,
@@ -0,0 +1,264 @@
+use crate::cell::Cell;
+use crate::color::{BlendMode, Color};
+use crate::console::Console;
+use crate::draw::DrawTarget;
+
+/// An offscreen console drawn over the root console when the frame is rendered.
+/// See [`LayerStack`].
+pub struct Layer {
+    /// the content of the layer
+    pub console: Console,
+    /// position of the layer's top-left corner on the root console
+    pub x: i32,
+    pub y: i32,
+    /// global opacity of the layer, between 0.0 (invisible) and 1.0
+    pub opacity: f32,
+    /// cells with this background color are not drawn
+    pub key_color: Option<Color>,
+    /// how the layer's colors are combined with the layers below
+    pub blend_mode: BlendMode,
+    /// hidden layers are not drawn
+    pub visible: bool,
+    /// id of the font used to draw this layer, as returned by [`DoryenApi::add_font`](crate::DoryenApi::add_font).
+    /// 0 is the main font.
+    pub font: usize,
+    /// offset in pixels (of the main font) added to the layer position
+    pub pixel_offset: (i32, i32),
+}
+
+impl Layer {
+    /// create a layer covering the top-left part of the root console.
+    /// All its cells are initially transparent (space character, background with alpha = 0).
+    pub fn new(width: u32, height: u32) -> Self {
+        let mut console = Console::new(width, height);
+        console.clear(None, Some((0, 0, 0, 0)), Some(' ' as u16));
+        Self {
+            console,
+            x: 0,
+            y: 0,
+            opacity: 1.0,
+            key_color: None,
+            blend_mode: BlendMode::Alpha,
+            visible: true,
+            font: 0,
+            pixel_offset: (0, 0),
+        }
+    }
+    /// Whether this layer is rendered in its own pass instead of being composited in the root console.
+    /// This is the case for layers using another font than the main one or with a pixel offset.
+    /// These layers are drawn with their own cell size over the root console using
+    /// alpha blending : the blend mode is ignored.
+    pub fn needs_pass(&self) -> bool {
+        self.font != 0 || self.pixel_offset != (0, 0)
+    }
+    /// position of the layer's top-left corner in pixels, for a main font with the given character size
+    pub fn pixel_position(&self, char_width: u32, char_height: u32) -> (i32, i32) {
+        (
+            self.x * char_width as i32 + self.pixel_offset.0,
+            self.y * char_height as i32 + self.pixel_offset.1,
+        )
+    }
+    /// copy the layer console in `out`, applying the opacity and key color to the colors alpha
+    pub(crate) fn prepare_pass(&self, out: &mut Console) {
+        out.copy_from(&self.console);
+        let (width, height) = out.get_size();
+        let pot_width = out.get_pot_width() as usize;
+        let scale = |c: Color| (c.0, c.1, c.2, (f32::from(c.3) * self.opacity) as u8);
+        for y in 0..height as usize {
+            for x in 0..width as usize {
+                let off = x + y * pot_width;
+                let back = out.borrow_background()[off];
+                if Some(back) == self.key_color {
+                    out.borrow_mut_background()[off] = (0, 0, 0, 0);
+                    out.borrow_mut_foreground()[off] = (0, 0, 0, 0);
+                } else {
+                    out.borrow_mut_background()[off] = scale(back);
+                    let fore = out.borrow_foreground()[off];
+                    out.borrow_mut_foreground()[off] = scale(fore);
+                }
+            }
+        }
+    }
+    /// whether this layer is drawn when the layers are composited over the root console
+    fn is_composited(&self) -> bool {
+        self.visible && self.opacity > 0.0 && !self.needs_pass()
+    }
+    /// the rectangle (x, y, width, height) covered by this layer on the root console
+    fn area(&self) -> (i32, i32, u32, u32) {
+        let (width, height) = self.console.get_size();
+        (self.x, self.y, width, height)
+    }
+    /// draw this layer on a console. Layers rendered in their own pass are ignored.
+    pub fn draw(&self, destination: &mut Console) {
+        if !self.is_composited() {
+            return;
+        }
+        let src = &self.console;
+        let (x, y, w, h) = self.area();
+        destination.mark_dirty(x, y, w, h);
+        let (width, height) = destination.get_size();
+        for y in 0.max(-self.y)..(src.get_height() as i32).min(height as i32 - self.y) {
+            for x in 0.max(-self.x)..(src.get_width() as i32).min(width as i32 - self.x) {
+                let src_idx = src.offset(x, y);
+                let dst_idx = destination.offset(x + self.x, y + self.y);
+                let src_back = src.borrow_background()[src_idx];
+                if Some(src_back) == self.key_color {
+                    continue;
+                }
+                destination.blend_cell(
+                    dst_idx,
+                    src,
+                    src_idx,
+                    self.blend_mode,
+                    self.opacity,
+                    self.opacity,
+                );
+            }
+        }
+    }
+}
+
+/// An ordered list of named layers drawn over the root console, the first one being at the bottom.
+/// The root console itself is never modified : layers are composited in a separate frame
+/// just before it is displayed (or captured).
+///
+/// Example
+/// ```
+/// use doryen_rs::{BlendMode, Console, Layer, LayerStack};
+/// let mut layers = LayerStack::new();
+/// let fog = layers.push("fog", Layer::new(10, 10));
+/// fog.blend_mode = BlendMode::Multiply;
+/// fog.console.back(2, 2, (128, 128, 128, 255));
+/// let mut root = Console::new(10, 10);
+/// root.clear(None, Some((200, 200, 200, 255)), None);
+/// let mut frame = Console::new(10, 10);
+/// layers.composite(&root, &mut frame);
+/// assert_eq!(frame.get_back(2, 2), Some((100, 100, 100, 255)));
+/// assert_eq!(frame.get_back(3, 2), Some((200, 200, 200, 255)));
+/// ```
+#[derive(Default)]
+pub struct LayerStack {
+    layers: Vec<(String, Layer)>,
+}
+
+impl LayerStack {
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// add a layer on top of the stack. If a layer with this name already exists, it is replaced
+    /// but keeps its position in the stack.
+    pub fn push(&mut self, name: &str, layer: Layer) -> &mut Layer {
+        let index = match self.index(name) {
+            Some(index) => {
+                self.layers[index].1 = layer;
+                index
+            }
+            None => {
+                self.layers.push((name.to_owned(), layer));
+                self.layers.len() - 1
+            }
+        };
+        &mut self.layers[index].1
+    }
+    /// insert a layer at a given position in the stack (0 = bottom).
+    /// An existing layer with the same name is removed first.
+    pub fn insert(&mut self, index: usize, name: &str, layer: Layer) -> &mut Layer {
+        self.remove(name);
+        let index = index.min(self.layers.len());
+        self.layers.insert(index, (name.to_owned(), layer));
+        &mut self.layers[index].1
+    }
+    pub fn remove(&mut self, name: &str) -> Option<Layer> {
+        self.index(name).map(|index| self.layers.remove(index).1)
+    }
+    pub fn get(&self, name: &str) -> Option<&Layer> {
+        self.index(name).map(|index| &self.layers[index].1)
+    }
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Layer> {
+        self.index(name).map(move |index| &mut self.layers[index].1)
+    }
+    /// position of a layer in the stack (0 = bottom)
+    pub fn index(&self, name: &str) -> Option<usize> {
+        self.layers.iter().position(|(n, _)| n == name)
+    }
+    pub fn len(&self) -> usize {
+        self.layers.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+    pub fn clear(&mut self) {
+        self.layers.clear();
+    }
+    /// iterate over the layers names and content, from bottom to top
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Layer)> {
+        self.layers
+            .iter()
+            .map(|(name, layer)| (name.as_str(), layer))
+    }
+    /// the areas of the root console covered by the layers drawn by [`LayerStack::composite`]
+    pub(crate) fn composited_areas(&self) -> Vec<(i32, i32, u32, u32)> {
+        self.layers
+            .iter()
+            .filter(|(_, layer)| layer.is_composited())
+            .map(|(_, layer)| layer.area())
+            .collect()
+    }
+    /// iterate over the visible layers rendered in their own pass, from bottom to top. See [`Layer::needs_pass`]
+    pub fn passes(&self) -> impl Iterator<Item = &Layer> {
+        self.layers
+            .iter()
+            .map(|(_, layer)| layer)
+            .filter(|layer| layer.visible && layer.opacity > 0.0 && layer.needs_pass())
+    }
+    /// copy the root console into `frame` and draw all the visible layers over it,
+    /// except those rendered in their own pass.
+    /// `frame` is resized to the root console size if needed.
+    pub fn composite(&self, root: &Console, frame: &mut Console) {
+        frame.copy_from(root);
+        for (_, layer) in self.layers.iter() {
+            layer.draw(frame);
+        }
+    }
+}
+
+/// Drawing on a layer draws on its console, in the layer's coordinates
+impl DrawTarget for Layer {
+    fn get_size(&self) -> (u32, u32) {
+        self.console.get_size()
+    }
+    fn get_cell(&self, x: i32, y: i32) -> Option<Cell> {
+        self.console.get_cell(x, y)
+    }
+    fn put_cell(
+        &mut self,
+        x: i32,
+        y: i32,
+        glyph: Option<u32>,
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) {
+        self.console.put_cell(x, y, glyph, fore, back);
+    }
+    fn map_char(&self, ch: char) -> u32 {
+        self.console.map_char(ch)
+    }
+    fn named_color(&self, name: &str) -> Option<Color> {
+        self.console.named_color(name)
+    }
+    fn named_glyph(&self, name: &str) -> Option<u32> {
+        self.console.named_glyph(name)
+    }
+    fn area(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        fore: Option<Color>,
+        back: Option<Color>,
+        fillchar: Option<u16>,
+    ) {
+        self.console.area(x, y, w, h, fore, back, fillchar);
+    }
+}
@@ -2,20 +2,57 @@ extern crate image;
 extern crate uni_app;
 extern crate uni_gl;
 
-pub use uni_app::{MouseButton, ScanCode};
+pub use uni_app::{AppEvent, KeyDownEvent, KeyUpEvent, MouseButton, MouseButtonEvent, ScanCode};
 
+mod ansi;
 mod app;
+mod cell;
 mod color;
 mod console;
+mod cp437;
+mod diff;
+mod draw;
+mod error;
 mod file;
 mod font;
+mod frame;
+mod glyph;
+mod headless;
 mod img;
 mod input;
+mod layer;
 mod program;
+mod renderer;
+mod serialize;
+mod terminal;
+#[cfg(feature = "ttf")]
+mod ttf;
+mod vfs;
+mod view;
+mod watch;
+mod xp;
 
 pub use self::app::*;
+pub use self::cell::{Cell, CellMut, Row, RowMut};
 pub use self::color::*;
 pub use self::console::*;
+pub use self::cp437::{char_to_cp437, cp437_to_char, CP437_TO_UNICODE};
+pub use self::diff::CellChange;
+pub use self::draw::DrawTarget;
+pub use self::error::LoadError;
 pub use self::file::FileLoader;
+pub use self::font::FontLoader;
+pub use self::frame::FrameStyle;
+pub use self::glyph::{FontLayout, GlyphMap};
+pub use self::headless::HeadlessApp;
 pub use self::img::*;
 pub use self::input::{InputApi, Keys};
+pub use self::layer::{Layer, LayerStack};
+pub use self::renderer::SoftwareRenderer;
+pub use self::serialize::CONSOLE_FORMAT_VERSION;
+pub use self::terminal::TerminalApp;
+#[cfg(feature = "ttf")]
+pub use self::ttf::TtfFont;
+pub use self::vfs::{with_vfs, Bundle, Vfs};
+pub use self::view::ConsoleView;
+pub use self::xp::{XpFile, XP_TRANSPARENT};
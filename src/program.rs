@@ -44,6 +44,26 @@ enum DoryenUniforms {
     TermCoef,
 }
 
+/// the textures containing the characters and colors of a console
+struct ConsoleTextures {
+    ascii: WebGLTexture,
+    foreground: WebGLTexture,
+    background: WebGLTexture,
+    /// size of the textures, (0, 0) before the first upload
+    size: (u32, u32),
+}
+
+impl ConsoleTextures {
+    fn new(gl: &WebGLRenderingContext) -> Self {
+        Self {
+            ascii: gl.create_texture(),
+            foreground: gl.create_texture(),
+            background: gl.create_texture(),
+            size: (0, 0),
+        }
+    }
+}
+
 pub struct Program {
     program: WebGLProgram,
     vao: WebGLVertexArray,
@@ -52,9 +72,10 @@ pub struct Program {
     vertex_pos_buffer: Option<WebGLBuffer>,
     vertex_uv_buffer: Option<WebGLBuffer>,
     font: Option<WebGLTexture>,
-    ascii: WebGLTexture,
-    foreground: WebGLTexture,
-    background: WebGLTexture,
+    /// textures of the displayed console, only updated where the console has changed
+    textures: ConsoleTextures,
+    /// textures of the layers rendered in their own pass, fully uploaded for each layer
+    pass_textures: ConsoleTextures,
     uniform_locations: HashMap<DoryenUniforms, Option<WebGLUniformLocation>>,
     data: PrimitiveData,
 }
@@ -146,9 +167,8 @@ impl Program {
             vertex_pos_buffer,
             vertex_uv_buffer,
             font: None,
-            ascii: gl.create_texture(),
-            foreground: gl.create_texture(),
-            background: gl.create_texture(),
+            textures: ConsoleTextures::new(gl),
+            pass_textures: ConsoleTextures::new(gl),
             uniform_locations,
             data,
         }
@@ -230,28 +250,92 @@ impl Program {
         }
     }
 
+    /// render the displayed console. Only the rows modified since the previous call
+    /// (see [`Console::dirty_rect`]) are uploaded to the GPU.
     pub fn render_primitive(&mut self, gl: &WebGLRenderingContext, con: &Console) {
-        self.set_uniforms(gl, con);
+        self.set_uniforms(gl, con, false);
+        self.draw(gl);
+    }
+
+    /// render a console whose content is unrelated to the previous call, like the layers rendered in their own pass
+    pub fn render_pass(&mut self, gl: &WebGLRenderingContext, con: &Console) {
+        self.set_uniforms(gl, con, true);
+        self.draw(gl);
+    }
+
+    fn set_uniforms(&mut self, gl: &WebGLRenderingContext, con: &Console, pass: bool) {
+        let textures = if pass {
+            &mut self.pass_textures
+        } else {
+            &mut self.textures
+        };
+        let size = (con.get_pot_width(), con.get_pot_height());
+        let upload = if pass || textures.size != size {
+            textures.size = size;
+            Upload::Full
+        } else {
+            match con.dirty_rect() {
+                Some((_, y, _, h)) => Upload::Rows(y as u32, y as u32 + h),
+                None => Upload::Nothing,
+            }
+        };
+        for (uniform, tex_num, tex, data) in [
+            (
+                DoryenUniforms::Ascii,
+                1,
+                &textures.ascii,
+                u32_to_u8(&con.borrow_ascii()[..]),
+            ),
+            (
+                DoryenUniforms::Foreground,
+                2,
+                &textures.foreground,
+                color_to_u8(&con.borrow_foreground()[..]),
+            ),
+            (
+                DoryenUniforms::Background,
+                3,
+                &textures.background,
+                color_to_u8(&con.borrow_background()[..]),
+            ),
+        ] {
+            if let Some(Some(location)) = self.uniform_locations.get(&uniform) {
+                update_uniform_texture(gl, location, tex_num, tex, data, size, upload);
+            }
+        }
+    }
 
+    fn draw(&self, gl: &WebGLRenderingContext) {
         gl.draw_arrays(
             self.data.draw_mode,
             self.data.count * self.data.data_per_primitive,
         );
     }
+}
 
-    fn update_uniform_texture(
-        &mut self,
-        gl: &WebGLRenderingContext,
-        uniform: DoryenUniforms,
-        tex_num: u32,
-        tex: &WebGLTexture,
-        data: &[u8],
-        pot_width: u32,
-        pot_height: u32,
-    ) {
-        if let Some(&Some(ref location)) = self.uniform_locations.get(&uniform) {
-            gl.active_texture(tex_num);
-            gl.bind_texture(tex);
+/// the part of a console texture to upload
+#[derive(Clone, Copy)]
+enum Upload {
+    /// (re)allocate the texture with the whole console
+    Full,
+    /// the rows between two y coordinates (exclusive)
+    Rows(u32, u32),
+    Nothing,
+}
+
+fn update_uniform_texture(
+    gl: &WebGLRenderingContext,
+    location: &WebGLUniformLocation,
+    tex_num: u32,
+    tex: &WebGLTexture,
+    data: &[u8],
+    (pot_width, pot_height): (u32, u32),
+    upload: Upload,
+) {
+    gl.active_texture(tex_num);
+    gl.bind_texture(tex);
+    match upload {
+        Upload::Full => {
             gl.tex_image2d(
                 TextureBindPoint::Texture2d, // target
                 0,                           // level
@@ -262,44 +346,25 @@ impl Program {
                 data,                        // data
             );
             set_texture_params(gl, true);
-            gl.uniform_1i(location, tex_num as i32);
         }
+        Upload::Rows(top, bottom) => {
+            // full rows are contiguous in the console buffers
+            let row_size = pot_width as usize * 4;
+            gl.tex_sub_image2d(
+                TextureBindPoint::Texture2d,
+                0,
+                0,
+                top as u16,
+                pot_width as u16,
+                (bottom - top) as u16,
+                PixelFormat::Rgba,
+                PixelType::UnsignedByte,
+                &data[top as usize * row_size..bottom as usize * row_size],
+            );
+        }
+        Upload::Nothing => (),
     }
-
-    pub fn set_uniforms(&mut self, gl: &WebGLRenderingContext, con: &Console) {
-        let pot_width = con.get_pot_width();
-        let pot_height = con.get_pot_height();
-        let ascii_tex = WebGLTexture(self.ascii.0);
-        self.update_uniform_texture(
-            gl,
-            DoryenUniforms::Ascii,
-            1,
-            &ascii_tex,
-            u32_to_u8(&con.borrow_ascii()[..]),
-            pot_width,
-            pot_height,
-        );
-        let fore_tex = WebGLTexture(self.foreground.0);
-        self.update_uniform_texture(
-            gl,
-            DoryenUniforms::Foreground,
-            2,
-            &fore_tex,
-            color_to_u8(&con.borrow_foreground()[..]),
-            pot_width,
-            pot_height,
-        );
-        let back_tex = WebGLTexture(self.background.0);
-        self.update_uniform_texture(
-            gl,
-            DoryenUniforms::Background,
-            3,
-            &back_tex,
-            color_to_u8(&con.borrow_background()[..]),
-            pot_width,
-            pot_height,
-        );
-    }
+    gl.uniform_1i(location, tex_num as i32);
 }
 
 fn set_buffer_data(
@@ -0,0 +1,147 @@
+use crate::color::Color;
+use crate::console::Console;
+use crate::font::FontLoader;
+
+/// A CPU implementation of the console fragment shader.
+///
+/// It composites each glyph of the font, multiplied by the cell foreground color,
+/// over the cell background color, exactly like the GLSL renderer does,
+/// but without needing a GL context. This makes it possible to render a console on a server
+/// or in automated tests.
+///
+/// Example
+/// ```
+/// use doryen_rs::{Console, SoftwareRenderer, TextAlign};
+/// // a 16x16 glyphs font with 8x8 characters. Every glyph is fully transparent here.
+/// let font = image::RgbaImage::new(128, 128);
+/// let renderer = SoftwareRenderer::new(font, 8, 8);
+/// let mut con = Console::new(10, 2);
+/// con.clear(None, Some((255, 0, 0, 255)), None);
+/// con.print(0, 0, "hello", TextAlign::Left, None, None);
+/// let img = renderer.render(&con);
+/// assert_eq!(img.dimensions(), (80, 16));
+/// assert_eq!(img.get_pixel(3, 3).0, [255, 0, 0, 255]);
+/// ```
+pub struct SoftwareRenderer {
+    font: image::RgbaImage,
+    char_width: u32,
+    char_height: u32,
+}
+
+impl SoftwareRenderer {
+    /// create a renderer from a font image and its character size in pixels.
+    /// The font image must already have been processed by [`FontLoader`] (transparent pixels have a 0 alpha).
+    pub fn new(font: image::RgbaImage, char_width: u32, char_height: u32) -> Self {
+        Self {
+            font,
+            char_width: char_width.max(1),
+            char_height: char_height.max(1),
+        }
+    }
+    /// create a renderer using the font loaded by a [`FontLoader`].
+    /// Returns None if the font has not been loaded yet.
+    pub fn from_font_loader(loader: &FontLoader) -> Option<Self> {
+        loader
+            .img
+            .as_ref()
+            .map(|img| Self::new(img.clone(), loader.char_width, loader.char_height))
+    }
+    /// return the size of a character in pixels
+    pub fn char_size(&self) -> (u32, u32) {
+        (self.char_width, self.char_height)
+    }
+    /// render a console to a new image.
+    /// The image size is (console width * char width, console height * char height)
+    pub fn render(&self, con: &Console) -> image::RgbaImage {
+        let mut img = image::RgbaImage::new(
+            con.get_width() * self.char_width,
+            con.get_height() * self.char_height,
+        );
+        self.render_into(con, &mut img);
+        img
+    }
+    /// render a console and encode the result as a PNG image
+    pub fn render_png(&self, con: &Console) -> Vec<u8> {
+        encode_png(&self.render(con))
+    }
+    /// render a console in an existing image. Pixels outside the image are ignored.
+    pub fn render_into(&self, con: &Console, img: &mut image::RgbaImage) {
+        let chars_per_line = (self.font.width() / self.char_width).max(1);
+        let pot_width = con.get_pot_width() as usize;
+        let ascii = con.borrow_ascii();
+        let fore = con.borrow_foreground();
+        let back = con.borrow_background();
+        let maxx = con.get_width().min(img.width() / self.char_width);
+        let maxy = con.get_height().min(img.height() / self.char_height);
+        for cy in 0..maxy {
+            for cx in 0..maxx {
+                let off = cx as usize + cy as usize * pot_width;
+                let code = ascii[off];
+                let glyph_x = (code % chars_per_line) * self.char_width;
+                let glyph_y = (code / chars_per_line) * self.char_height;
+                for py in 0..self.char_height {
+                    for px in 0..self.char_width {
+                        let font_color = self.font_pixel(glyph_x + px, glyph_y + py);
+                        let color = composite(font_color, fore[off], back[off]);
+                        img.put_pixel(
+                            cx * self.char_width + px,
+                            cy * self.char_height + py,
+                            image::Rgba([color.0, color.1, color.2, color.3]),
+                        );
+                    }
+                }
+            }
+        }
+    }
+    /// render a console over an existing image with alpha blending, its top-left corner
+    /// being at pixel position (x, y). Pixels outside the image are ignored.
+    pub fn render_over(&self, con: &Console, img: &mut image::RgbaImage, x: i32, y: i32) {
+        let src = self.render(con);
+        for (sx, sy, pixel) in src.enumerate_pixels() {
+            let (dx, dy) = (x + sx as i32, y + sy as i32);
+            if dx < 0 || dy < 0 || dx as u32 >= img.width() || dy as u32 >= img.height() {
+                continue;
+            }
+            let alpha = f32::from(pixel[3]) / 255.0;
+            let dst = img.get_pixel_mut(dx as u32, dy as u32);
+            for i in 0..3 {
+                dst[i] =
+                    (f32::from(pixel[i]) * alpha + f32::from(dst[i]) * (1.0 - alpha)).round() as u8;
+            }
+            dst[3] = dst[3].max(pixel[3]);
+        }
+    }
+    fn font_pixel(&self, x: u32, y: u32) -> Color {
+        if x >= self.font.width() || y >= self.font.height() {
+            return (0, 0, 0, 0);
+        }
+        let p = self.font.get_pixel(x, y);
+        (p[0], p[1], p[2], p[3])
+    }
+}
+
+/// same formula as the fragment shader :
+/// `font.a * foreground * vec4(font.rgb, 1.0) + (1.0 - font.a) * background`
+fn composite(font: Color, fore: Color, back: Color) -> Color {
+    let alpha = f32::from(font.3) / 255.0;
+    let channel = |f: u8, fg: u8, bg: u8| {
+        let f = f32::from(f) / 255.0;
+        let fg = f32::from(fg) / 255.0;
+        let bg = f32::from(bg) / 255.0;
+        ((alpha * fg * f + (1.0 - alpha) * bg) * 255.0).round() as u8
+    };
+    (
+        channel(font.0, fore.0, back.0),
+        channel(font.1, fore.1, back.1),
+        channel(font.2, fore.2, back.2),
+        channel(255, fore.3, back.3),
+    )
+}
+
+pub(crate) fn encode_png(img: &image::RgbaImage) -> Vec<u8> {
+    let mut png = Vec::new();
+    image::png::PngEncoder::new(&mut png)
+        .encode(img, img.width(), img.height(), image::ColorType::Rgba8)
+        .expect("Failed to encode PNG image");
+    png
+}
@@ -0,0 +1,258 @@
+use std::collections::BTreeMap;
+
+use crate::color::Color;
+use crate::console::Console;
+
+const MAGIC: &[u8; 4] = b"DRYC";
+/// current version of the console serialization format
+pub const CONSOLE_FORMAT_VERSION: u16 = 1;
+
+impl Console {
+    /// Serialize the console in a compact binary format.
+    /// This includes the console size, the content of every cell and the colors registered with [`Console::register_color`].
+    ///
+    /// Example
+    /// ```
+    /// use doryen_rs::{Console, TextAlign};
+    /// let mut con = Console::new(20, 4);
+    /// con.register_color("red", (255, 0, 0, 255));
+    /// con.print(1, 1, "hello", TextAlign::Left, Some((0, 255, 0, 255)), None);
+    /// let copy = Console::from_bytes(&con.to_bytes()).unwrap();
+    /// assert_eq!(copy.get_size(), (20, 4));
+    /// assert_eq!(copy.get_ascii(1, 1), Some('h' as u16));
+    /// assert_eq!(copy.get_fore(1, 1), Some((0, 255, 0, 255)));
+    /// assert_eq!(copy.get_color("red"), Some((255, 0, 0, 255)));
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(MAGIC);
+        buf.extend_from_slice(&CONSOLE_FORMAT_VERSION.to_le_bytes());
+        buf.extend_from_slice(&self.get_width().to_le_bytes());
+        buf.extend_from_slice(&self.get_height().to_le_bytes());
+        // sorted so that the same console always gives the same bytes
+        let colors: BTreeMap<&String, &Color> = self.colors().iter().collect();
+        buf.extend_from_slice(&(colors.len() as u32).to_le_bytes());
+        for (name, color) in colors {
+            buf.extend_from_slice(&(name.len() as u32).to_le_bytes());
+            buf.extend_from_slice(name.as_bytes());
+            buf.extend_from_slice(&[color.0, color.1, color.2, color.3]);
+        }
+        let pot_width = self.get_pot_width() as usize;
+        for y in 0..self.get_height() as usize {
+            for x in 0..self.get_width() as usize {
+                let off = x + y * pot_width;
+                let fore = self.borrow_foreground()[off];
+                let back = self.borrow_background()[off];
+                buf.extend_from_slice(&self.borrow_ascii()[off].to_le_bytes());
+                buf.extend_from_slice(&[fore.0, fore.1, fore.2, fore.3]);
+                buf.extend_from_slice(&[back.0, back.1, back.2, back.3]);
+            }
+        }
+        buf
+    }
+    /// Create a console from data produced by [`Console::to_bytes`]
+    pub fn from_bytes(buf: &[u8]) -> Result<Console, String> {
+        let mut reader = ByteReader::new(buf, "console data");
+        if reader.read_bytes(4)? != MAGIC {
+            return Err("Not a doryen console".to_owned());
+        }
+        let version = reader.read_u16()?;
+        if version > CONSOLE_FORMAT_VERSION {
+            return Err(format!(
+                "Unsupported console format version {} (max {})",
+                version, CONSOLE_FORMAT_VERSION
+            ));
+        }
+        let width = reader.read_u32()?;
+        let height = reader.read_u32()?;
+        check_console_size(width, height)?;
+        let color_count = reader.read_u32()?;
+        let mut colors = Vec::new();
+        for _ in 0..color_count {
+            let len = reader.read_u32()? as usize;
+            let name = std::str::from_utf8(reader.read_bytes(len)?)
+                .map_err(|e| format!("Invalid color name : {}", e))?;
+            colors.push((name, reader.read_color()?));
+        }
+        // each cell uses 12 bytes : glyph (4), foreground (4) and background (4)
+        let size = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|cells| cells.checked_mul(12));
+        if size.is_none_or(|size| size > reader.remaining()) {
+            return Err(format!(
+                "Invalid console size {} x {} : unexpected end of console data",
+                width, height
+            ));
+        }
+        let mut con = Console::new(width, height);
+        for (name, color) in colors {
+            con.register_color(name, color);
+        }
+        let pot_width = con.get_pot_width() as usize;
+        for y in 0..height as usize {
+            for x in 0..width as usize {
+                let off = x + y * pot_width;
+                let ascii = reader.read_u32()?;
+                let fore = reader.read_color()?;
+                let back = reader.read_color()?;
+                con.borrow_mut_ascii()[off] = ascii;
+                con.borrow_mut_foreground()[off] = fore;
+                con.borrow_mut_background()[off] = back;
+            }
+        }
+        Ok(con)
+    }
+}
+
+/// largest number of cells, power of 2 padding included, of a deserialized console
+const MAX_CELLS: u64 = 1 << 24;
+
+/// check that a console read from external data can be allocated : both dimensions are 0 or none is,
+/// and the padded buffers are not too large
+fn check_console_size(width: u32, height: u32) -> Result<(), String> {
+    let padded = u64::from(width)
+        .next_power_of_two()
+        .saturating_mul(u64::from(height).next_power_of_two());
+    if (width == 0) != (height == 0) || padded > MAX_CELLS {
+        return Err(format!("Invalid console size {} x {}", width, height));
+    }
+    Ok(())
+}
+
+/// little endian binary data reader used by the console file formats
+pub(crate) struct ByteReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    /// name of the format, for error messages
+    format: &'static str,
+}
+
+impl<'a> ByteReader<'a> {
+    pub(crate) fn new(data: &'a [u8], format: &'static str) -> Self {
+        Self {
+            data,
+            pos: 0,
+            format,
+        }
+    }
+    /// number of bytes not read yet
+    pub(crate) fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+    pub(crate) fn read_bytes(&mut self, count: usize) -> Result<&'a [u8], String> {
+        if self.pos + count > self.data.len() {
+            return Err(format!("Unexpected end of {}", self.format));
+        }
+        let bytes = &self.data[self.pos..self.pos + count];
+        self.pos += count;
+        Ok(bytes)
+    }
+    pub(crate) fn read_u16(&mut self) -> Result<u16, String> {
+        let b = self.read_bytes(2)?;
+        Ok(u16::from_le_bytes([b[0], b[1]]))
+    }
+    pub(crate) fn read_u32(&mut self) -> Result<u32, String> {
+        let b = self.read_bytes(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+    pub(crate) fn read_i32(&mut self) -> Result<i32, String> {
+        let b = self.read_bytes(4)?;
+        Ok(i32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+    /// read an opaque color stored as 3 bytes
+    pub(crate) fn read_rgb(&mut self) -> Result<Color, String> {
+        let b = self.read_bytes(3)?;
+        Ok((b[0], b[1], b[2], 255))
+    }
+    pub(crate) fn read_color(&mut self) -> Result<Color, String> {
+        let b = self.read_bytes(4)?;
+        Ok((b[0], b[1], b[2], b[3]))
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use std::collections::BTreeMap;
+
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::{check_console_size, CONSOLE_FORMAT_VERSION};
+    use crate::color::Color;
+    use crate::console::Console;
+
+    /// what is actually serialized. Cells are stored row by row, without the power of 2 padding.
+    #[derive(Serialize, Deserialize)]
+    struct ConsoleData {
+        version: u16,
+        width: u32,
+        height: u32,
+        colors: BTreeMap<String, Color>,
+        ascii: Vec<u32>,
+        fore: Vec<Color>,
+        back: Vec<Color>,
+    }
+
+    impl Serialize for Console {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let (width, height) = self.get_size();
+            let pot_width = self.get_pot_width() as usize;
+            let mut data = ConsoleData {
+                version: CONSOLE_FORMAT_VERSION,
+                width,
+                height,
+                colors: self
+                    .colors()
+                    .iter()
+                    .map(|(name, color)| (name.clone(), *color))
+                    .collect(),
+                ascii: Vec::new(),
+                fore: Vec::new(),
+                back: Vec::new(),
+            };
+            for y in 0..height as usize {
+                let range = y * pot_width..y * pot_width + width as usize;
+                data.ascii
+                    .extend_from_slice(&self.borrow_ascii()[range.clone()]);
+                data.fore
+                    .extend_from_slice(&self.borrow_foreground()[range.clone()]);
+                data.back
+                    .extend_from_slice(&self.borrow_background()[range]);
+            }
+            data.serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Console {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let data = ConsoleData::deserialize(deserializer)?;
+            if data.version > CONSOLE_FORMAT_VERSION {
+                return Err(de::Error::custom(format!(
+                    "Unsupported console format version {} (max {})",
+                    data.version, CONSOLE_FORMAT_VERSION
+                )));
+            }
+            check_console_size(data.width, data.height).map_err(de::Error::custom)?;
+            let count = (data.width as usize).checked_mul(data.height as usize);
+            if count.is_none_or(|count| {
+                data.ascii.len() != count || data.fore.len() != count || data.back.len() != count
+            }) {
+                return Err(de::Error::custom(
+                    "Console cell count doesn't match its size",
+                ));
+            }
+            let mut con = Console::new(data.width, data.height);
+            for (name, color) in data.colors.iter() {
+                con.register_color(name, *color);
+            }
+            let pot_width = con.get_pot_width() as usize;
+            for y in 0..data.height as usize {
+                let src = y * data.width as usize..(y + 1) * data.width as usize;
+                let dst = y * pot_width..y * pot_width + data.width as usize;
+                con.borrow_mut_ascii()[dst.clone()].copy_from_slice(&data.ascii[src.clone()]);
+                con.borrow_mut_foreground()[dst.clone()].copy_from_slice(&data.fore[src.clone()]);
+                con.borrow_mut_background()[dst].copy_from_slice(&data.back[src]);
+            }
+            Ok(con)
+        }
+    }
+}
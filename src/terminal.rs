@@ -0,0 +1,565 @@
+use std::io::{self, Write};
+
+use uni_app::{AppEvent, KeyDownEvent, KeyUpEvent, MouseButton, MouseButtonEvent, ScanCode};
+
+use crate::ansi::{glyph_to_char, is_wide_glyph};
+use crate::app::{reload_assets, update_ticks, AppOptions, DoryenApiImpl, Engine, UpdateEvent};
+use crate::console::Console;
+use crate::input::DoryenInput;
+
+const CSI: &str = "\x1b[";
+
+/// An alternative to [`App`](crate::App) that displays the root console in a text terminal
+/// using ANSI escape sequences instead of OpenGL.
+///
+/// Only the cells that changed since the previous frame are sent to the terminal.
+/// Glyphs below 256 are considered CP437 codes and converted to unicode characters,
+/// so the game should be designed for a CP437 font.
+///
+/// Keyboard and mouse escape sequences sent by the terminal are translated into [`InputApi`](crate::InputApi) events.
+/// Since terminals don't report key releases, a key is released after the first update following its press.
+/// Mouse coordinates are in console cells. Ctrl-C is reported as a close request.
+///
+/// The output can be any writer, which makes it possible to test the game with an in-memory buffer.
+///
+/// Example
+/// ```
+/// use doryen_rs::{AppOptions, DoryenApi, Engine, TerminalApp, TextAlign};
+/// struct Hello;
+/// impl Engine for Hello {
+///     fn render(&mut self, api: &mut dyn DoryenApi) {
+///         api.con().print(0, 0, "hello", TextAlign::Left, None, None);
+///     }
+/// }
+/// let mut app = TerminalApp::new(AppOptions { console_width: 10, console_height: 2, ..Default::default() }, Vec::new());
+/// app.set_engine(Box::new(Hello));
+/// app.step(1.0).unwrap();
+/// assert!(String::from_utf8_lossy(app.output()).contains("hello"));
+/// ```
+pub struct TerminalApp<W: Write> {
+    api: DoryenApiImpl,
+    engine: Option<Box<dyn Engine>>,
+    options: AppOptions,
+    output: W,
+    /// content of the terminal after the last frame
+    previous: Console,
+    pressed_keys: Vec<ScanCode>,
+    /// end of the input received so far that is not a complete sequence or UTF-8 character
+    pending_input: Vec<u8>,
+    /// whether input was received since the last step
+    input_fed: bool,
+    next_tick: f64,
+    initialized: bool,
+    exited: bool,
+}
+
+impl<W: Write> TerminalApp<W> {
+    pub fn new(options: AppOptions, output: W) -> Self {
+        // one pixel = one console cell
+        let input = DoryenInput::new(
+            (options.console_width, options.console_height),
+            (options.console_width, options.console_height),
+            (0, 0),
+        );
+        Self {
+            api: DoryenApiImpl::new(&options, input),
+            engine: None,
+            options,
+            output,
+            previous: Console::new(0, 0),
+            pressed_keys: Vec::new(),
+            pending_input: Vec::new(),
+            input_fed: false,
+            next_tick: 0.0,
+            initialized: false,
+            exited: false,
+        }
+    }
+    pub fn set_engine(&mut self, engine: Box<dyn Engine>) {
+        self.engine = Some(engine);
+        self.initialized = false;
+    }
+    /// the writer where the console is displayed
+    pub fn output(&self) -> &W {
+        &self.output
+    }
+    /// whether the engine has returned [`UpdateEvent::Exit`]
+    pub fn has_exited(&self) -> bool {
+        self.exited
+    }
+    /// Translate bytes received from the terminal into input events.
+    /// An escape sequence or a UTF-8 character cut at the end of `bytes` is completed by the next call.
+    /// If no input is received until the next [`TerminalApp::step`], it's translated as it is
+    /// (a lone escape character is the Escape key).
+    pub fn feed_input(&mut self, bytes: &[u8]) {
+        self.pending_input.extend_from_slice(bytes);
+        self.input_fed = true;
+        self.parse_input(false);
+    }
+    fn parse_input(&mut self, complete: bool) {
+        let (events, used) = parse_terminal_input(&self.pending_input, complete);
+        self.pending_input.drain(..used);
+        for evt in events {
+            match evt {
+                AppEvent::KeyDown(ref key) => self.pressed_keys.push(key.code),
+                AppEvent::CloseRequested if !self.options.intercept_close_request => {
+                    self.exited = true;
+                }
+                _ => (),
+            }
+            self.api.input.on_event(&evt);
+        }
+    }
+    /// Run all the updates until `time` (in seconds), then render the root console to the terminal.
+    /// Returns false if the engine has returned [`UpdateEvent::Exit`].
+    pub fn step(&mut self, time: f64) -> io::Result<bool> {
+        if !self.input_fed && !self.pending_input.is_empty() {
+            self.parse_input(true);
+        }
+        self.input_fed = false;
+        if self.exited {
+            return Ok(false);
+        }
+        let mut engine = match self.engine.take() {
+            Some(engine) => engine,
+            None => return Ok(false),
+        };
+        if !self.initialized {
+            engine.init(&mut self.api);
+            self.initialized = true;
+        }
+        reload_assets(&mut *engine, &mut self.api, time);
+        // fonts are not used in a terminal
+        self.api.clear_font_path();
+        let next_tick_before = self.next_tick;
+        for event in update_ticks(
+            &mut *engine,
+            &mut self.api,
+            time,
+            &mut self.next_tick,
+            &mut |_| (),
+        ) {
+            match event {
+                UpdateEvent::Capture(filepath) => self.api.save_capture(&filepath),
+                UpdateEvent::Exit => self.exited = true,
+            }
+        }
+        if self.next_tick > next_tick_before {
+            self.release_keys();
+        }
+        engine.render(&mut self.api);
+        self.api.compose_frame();
+        self.engine = Some(engine);
+        self.draw()?;
+        Ok(!self.exited)
+    }
+    fn release_keys(&mut self) {
+        for code in self.pressed_keys.drain(..) {
+            self.api.input.on_event(&AppEvent::KeyUp(KeyUpEvent {
+                code,
+                key: format!("{:?}", code),
+                shift: false,
+                alt: false,
+                ctrl: false,
+            }));
+        }
+    }
+    /// send the cells that changed since the previous frame to the terminal
+    fn draw(&mut self) -> io::Result<()> {
+        let con = self.api.frame();
+        let mut out = String::new();
+        let resized = self.previous.get_size() != con.get_size();
+        if resized {
+            out.push_str(CSI);
+            out.push_str("0m");
+            out.push_str(CSI);
+            out.push_str("2J");
+        }
+        let mut cells = Vec::new();
+        for change in con.diff(&self.previous) {
+            cells.push((change.x, change.y));
+            // the previous cell was a wide character that was overwritten :
+            // the terminal erased the next cell too
+            if !resized && is_wide_glyph(self.previous.get_glyph(change.x, change.y).unwrap_or(0)) {
+                cells.push((change.x + 1, change.y));
+            }
+        }
+        cells.dedup();
+        let mut cursor = None;
+        let mut fore = None;
+        let mut back = None;
+        for (x, y) in cells {
+            let cell = match con.get_cell(x, y) {
+                Some(cell) => cell,
+                None => continue,
+            };
+            if x > 0
+                && con
+                    .get_cell(x - 1, y)
+                    .is_some_and(|left| is_wide_glyph(left.glyph))
+            {
+                // second half of a wide character, already displayed by the terminal
+                continue;
+            }
+            if cursor != Some((x, y)) {
+                out.push_str(&format!("{}{};{}H", CSI, y + 1, x + 1));
+            }
+            if fore != Some(cell.fore) {
+                out.push_str(&format!(
+                    "{}38;2;{};{};{}m",
+                    CSI, cell.fore.0, cell.fore.1, cell.fore.2
+                ));
+                fore = Some(cell.fore);
+            }
+            if back != Some(cell.back) {
+                out.push_str(&format!(
+                    "{}48;2;{};{};{}m",
+                    CSI, cell.back.0, cell.back.1, cell.back.2
+                ));
+                back = Some(cell.back);
+            }
+            out.push(glyph_to_char(cell.glyph));
+            let width = if is_wide_glyph(cell.glyph) { 2 } else { 1 };
+            cursor = Some((x + width, y));
+        }
+        self.previous.copy_from(con);
+        if !out.is_empty() {
+            self.output.write_all(out.as_bytes())?;
+            self.output.flush()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+impl TerminalApp<io::Stdout> {
+    /// Create a terminal application writing to the standard output.
+    pub fn new_stdout(options: AppOptions) -> Self {
+        Self::new(options, io::stdout())
+    }
+    /// Run the game loop in the current terminal until the engine exits.
+    /// The terminal is switched to raw mode with mouse reporting during the game.
+    pub fn run(mut self) -> io::Result<()> {
+        use std::process::{Command, Stdio};
+        use std::sync::mpsc;
+        use std::time::{Duration, Instant};
+
+        let saved_mode = Command::new("stty")
+            .arg("-g")
+            .stdin(Stdio::inherit())
+            .output()?;
+        let saved_mode = String::from_utf8_lossy(&saved_mode.stdout)
+            .trim()
+            .to_owned();
+        Command::new("stty")
+            .args(["raw", "-echo"])
+            .stdin(Stdio::inherit())
+            .status()?;
+        let _restore = TerminalRestore { saved_mode };
+        // alternate screen, hidden cursor, mouse button/motion reporting with SGR coordinates
+        self.output
+            .write_all(b"\x1b[?1049h\x1b[?25l\x1b[?1003h\x1b[?1006h")?;
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            use std::io::Read;
+            let mut buf = [0u8; 256];
+            let mut stdin = io::stdin();
+            while let Ok(count) = stdin.read(&mut buf) {
+                if count == 0 || sender.send(buf[..count].to_vec()).is_err() {
+                    break;
+                }
+            }
+        });
+        let start = Instant::now();
+        let frame_duration = if self.options.max_fps > 0 {
+            Duration::from_secs_f64(1.0 / self.options.max_fps as f64)
+        } else {
+            Duration::from_secs_f64(1.0 / 60.0)
+        };
+        loop {
+            while let Ok(bytes) = receiver.try_recv() {
+                self.feed_input(&bytes);
+            }
+            if !self.step(start.elapsed().as_secs_f64())? {
+                return Ok(());
+            }
+            std::thread::sleep(frame_duration);
+        }
+    }
+}
+
+/// Restores the terminal state changed by [`TerminalApp::run`] when dropped,
+/// whether the game loop ends normally, with an error or with a panic.
+#[cfg(unix)]
+struct TerminalRestore {
+    /// terminal settings saved by `stty -g`
+    saved_mode: String,
+}
+
+#[cfg(unix)]
+impl Drop for TerminalRestore {
+    fn drop(&mut self) {
+        use std::process::{Command, Stdio};
+
+        let mut stdout = io::stdout();
+        // errors are ignored : there's nothing better to do than trying every step
+        let _ = stdout.write_all(b"\x1b[?1006l\x1b[?1003l\x1b[0m\x1b[?25h\x1b[?1049l");
+        let _ = stdout.flush();
+        let _ = Command::new("stty")
+            .arg(&self.saved_mode)
+            .stdin(Stdio::inherit())
+            .status();
+    }
+}
+
+/// Translate the bytes sent by a terminal into keyboard and mouse events.
+/// Returns the events and the number of bytes used : an escape sequence or a UTF-8 character
+/// cut at the end of `bytes` is not used, unless `complete` is true.
+pub(crate) fn parse_terminal_input(bytes: &[u8], complete: bool) -> (Vec<AppEvent>, usize) {
+    let mut events = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != 0x1b {
+            match next_char(&bytes[i..], complete) {
+                Some((ch, len)) => {
+                    push_char(&mut events, ch);
+                    i += len;
+                }
+                None => break,
+            }
+            continue;
+        }
+        let escape = i;
+        i += 1;
+        match bytes.get(i) {
+            None if complete => push_key(&mut events, ScanCode::Escape, false, false, false),
+            None => return (events, escape),
+            Some(b'[') => {
+                let start = i + 1;
+                let mut end = start;
+                while end < bytes.len() && !(0x40..=0x7e).contains(&bytes[end]) {
+                    end += 1;
+                }
+                if end >= bytes.len() {
+                    return (events, if complete { bytes.len() } else { escape });
+                }
+                let params = String::from_utf8_lossy(&bytes[start..end]);
+                csi_event(&mut events, &params, char::from(bytes[end]));
+                i = end + 1;
+            }
+            Some(b'O') if i + 1 < bytes.len() => {
+                let code = match bytes[i + 1] {
+                    b'P' => Some(ScanCode::F1),
+                    b'Q' => Some(ScanCode::F2),
+                    b'R' => Some(ScanCode::F3),
+                    b'S' => Some(ScanCode::F4),
+                    c => arrow_key(char::from(c)),
+                };
+                if let Some(code) = code {
+                    push_key(&mut events, code, false, false, false);
+                }
+                i += 2;
+            }
+            Some(b'O') if !complete => return (events, escape),
+            Some(_) => match next_char(&bytes[i..], complete) {
+                Some((c, len)) => {
+                    // alt + key
+                    if let Some((code, shift)) = char_scan_code(c) {
+                        push_key(&mut events, code, shift, true, false);
+                    }
+                    i += len;
+                }
+                None => return (events, escape),
+            },
+        }
+    }
+    (events, i)
+}
+
+/// The first UTF-8 character of `bytes` and its length in bytes. Invalid bytes are replaced by U+FFFD.
+/// Returns None if the character is cut at the end of `bytes`, unless `complete` is true.
+fn next_char(bytes: &[u8], complete: bool) -> Option<(char, usize)> {
+    let len = match bytes[0] {
+        0xc2..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf4 => 4,
+        _ => 1,
+    };
+    if len > bytes.len() {
+        let cut = 1 + bytes[1..].iter().take_while(|&&b| b & 0xc0 == 0x80).count();
+        if cut == bytes.len() && !complete {
+            return None;
+        }
+        return Some((char::REPLACEMENT_CHARACTER, cut));
+    }
+    match std::str::from_utf8(&bytes[..len]) {
+        Ok(text) => text.chars().next().map(|ch| (ch, len)),
+        Err(e) => Some((char::REPLACEMENT_CHARACTER, e.error_len().unwrap_or(1))),
+    }
+}
+
+fn push_key(events: &mut Vec<AppEvent>, code: ScanCode, shift: bool, alt: bool, ctrl: bool) {
+    events.push(AppEvent::KeyDown(KeyDownEvent {
+        code,
+        key: format!("{:?}", code),
+        shift,
+        alt,
+        ctrl,
+    }));
+}
+
+fn push_char(events: &mut Vec<AppEvent>, ch: char) {
+    match ch {
+        '\x03' => events.push(AppEvent::CloseRequested),
+        '\r' | '\n' => push_key(events, ScanCode::Enter, false, false, false),
+        '\t' => push_key(events, ScanCode::Tab, false, false, false),
+        '\x08' | '\x7f' => push_key(events, ScanCode::Backspace, false, false, false),
+        '\x01'..='\x1a' => {
+            let letter = (b'a' + ch as u8 - 1) as char;
+            if let Some((code, _)) = char_scan_code(letter) {
+                push_key(events, code, false, false, true);
+            }
+        }
+        _ if ch.is_control() => (),
+        _ => {
+            if let Some((code, shift)) = char_scan_code(ch) {
+                push_key(events, code, shift, false, false);
+            }
+            events.push(AppEvent::CharEvent(ch));
+        }
+    }
+}
+
+fn csi_event(events: &mut Vec<AppEvent>, params: &str, command: char) {
+    if let Some(mouse) = params.strip_prefix('<') {
+        // SGR mouse report : button;x;y followed by M (press/motion) or m (release)
+        let values: Vec<u32> = mouse.split(';').filter_map(|v| v.parse().ok()).collect();
+        if let [button, x, y] = values[..] {
+            events.push(AppEvent::MousePos((
+                f64::from(x.max(1) - 1),
+                f64::from(y.max(1) - 1),
+            )));
+            let motion = button & 32 != 0;
+            let wheel = button & 64 != 0;
+            if !motion && !wheel {
+                let button = match button & 3 {
+                    0 => MouseButton::Left,
+                    1 => MouseButton::Middle,
+                    _ => MouseButton::Right,
+                };
+                events.push(if command == 'M' {
+                    AppEvent::MouseDown(MouseButtonEvent { button })
+                } else {
+                    AppEvent::MouseUp(MouseButtonEvent { button })
+                });
+            }
+        }
+        return;
+    }
+    let values: Vec<u32> = params.split(';').filter_map(|v| v.parse().ok()).collect();
+    // xterm modifier parameter : 1 + (shift=1 | alt=2 | ctrl=4)
+    let modifiers = values.get(1).copied().unwrap_or(1).max(1) - 1;
+    let (shift, alt, ctrl) = (modifiers & 1 != 0, modifiers & 2 != 0, modifiers & 4 != 0);
+    let code = match command {
+        '~' => match values.first().copied().unwrap_or(0) {
+            1 | 7 => Some(ScanCode::Home),
+            2 => Some(ScanCode::Insert),
+            3 => Some(ScanCode::Delete),
+            4 | 8 => Some(ScanCode::End),
+            5 => Some(ScanCode::PageUp),
+            6 => Some(ScanCode::PageDown),
+            11 => Some(ScanCode::F1),
+            12 => Some(ScanCode::F2),
+            13 => Some(ScanCode::F3),
+            14 => Some(ScanCode::F4),
+            15 => Some(ScanCode::F5),
+            17 => Some(ScanCode::F6),
+            18 => Some(ScanCode::F7),
+            19 => Some(ScanCode::F8),
+            20 => Some(ScanCode::F9),
+            21 => Some(ScanCode::F10),
+            23 => Some(ScanCode::F11),
+            24 => Some(ScanCode::F12),
+            _ => None,
+        },
+        'Z' => Some(ScanCode::Tab),
+        c => arrow_key(c),
+    };
+    if let Some(code) = code {
+        push_key(events, code, shift || command == 'Z', alt, ctrl);
+    }
+}
+
+fn arrow_key(c: char) -> Option<ScanCode> {
+    match c {
+        'A' => Some(ScanCode::Up),
+        'B' => Some(ScanCode::Down),
+        'C' => Some(ScanCode::Right),
+        'D' => Some(ScanCode::Left),
+        'H' => Some(ScanCode::Home),
+        'F' => Some(ScanCode::End),
+        _ => None,
+    }
+}
+
+/// scan code of the key producing a character on a qwerty keyboard, and whether shift is needed
+fn char_scan_code(ch: char) -> Option<(ScanCode, bool)> {
+    const LETTERS: [ScanCode; 26] = [
+        ScanCode::A,
+        ScanCode::B,
+        ScanCode::C,
+        ScanCode::D,
+        ScanCode::E,
+        ScanCode::F,
+        ScanCode::G,
+        ScanCode::H,
+        ScanCode::I,
+        ScanCode::J,
+        ScanCode::K,
+        ScanCode::L,
+        ScanCode::M,
+        ScanCode::N,
+        ScanCode::O,
+        ScanCode::P,
+        ScanCode::Q,
+        ScanCode::R,
+        ScanCode::S,
+        ScanCode::T,
+        ScanCode::U,
+        ScanCode::V,
+        ScanCode::W,
+        ScanCode::X,
+        ScanCode::Y,
+        ScanCode::Z,
+    ];
+    const DIGITS: [ScanCode; 10] = [
+        ScanCode::Key0,
+        ScanCode::Key1,
+        ScanCode::Key2,
+        ScanCode::Key3,
+        ScanCode::Key4,
+        ScanCode::Key5,
+        ScanCode::Key6,
+        ScanCode::Key7,
+        ScanCode::Key8,
+        ScanCode::Key9,
+    ];
+    match ch {
+        'a'..='z' => Some((LETTERS[(ch as u8 - b'a') as usize], false)),
+        'A'..='Z' => Some((LETTERS[(ch as u8 - b'A') as usize], true)),
+        '0'..='9' => Some((DIGITS[(ch as u8 - b'0') as usize], false)),
+        ' ' => Some((ScanCode::Space, false)),
+        '-' => Some((ScanCode::Minus, false)),
+        '=' => Some((ScanCode::Equal, false)),
+        ',' => Some((ScanCode::Comma, false)),
+        '.' => Some((ScanCode::Period, false)),
+        '/' => Some((ScanCode::Slash, false)),
+        ';' => Some((ScanCode::Semicolon, false)),
+        '\'' => Some((ScanCode::Apostrophe, false)),
+        '[' => Some((ScanCode::LBracket, false)),
+        ']' => Some((ScanCode::RBracket, false)),
+        '\\' => Some((ScanCode::Backslash, false)),
+        '`' => Some((ScanCode::Backquote, false)),
+        _ => None,
+    }
+}
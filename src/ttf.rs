@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+
+use ab_glyph::{point, Font, FontVec, PxScale, ScaleFont};
+
+use crate::console::Console;
+
+/// number of glyphs per row in the atlas
+const ATLAS_COLUMNS: u32 = 16;
+
+/// A TrueType/OpenType font rasterized at runtime into a doryen font atlas.
+///
+/// Glyphs are rasterized in white the first time they are used, so the atlas only contains
+/// the characters actually displayed. Consoles still contain unicode code points
+/// (use [`crate::FontLayout::Codepoint`]) : they are converted into atlas indices with [`TtfFont::remap`]
+/// just before rendering.
+///
+/// You usually don't use this directly : [`crate::DoryenApi::set_font_path`] loads .ttf and .otf files
+/// when the `ttf` feature is enabled.
+///
+/// Example
+/// ```no_run
+/// use doryen_rs::{Console, TextAlign, TtfFont};
+/// let data = std::fs::read("DejaVuSansMono.ttf").unwrap();
+/// let mut font = TtfFont::from_bytes(data, 10, 20).unwrap();
+/// let mut con = Console::new(10, 1);
+/// con.print(0, 0, "Привет", TextAlign::Left, None, None);
+/// font.remap(&mut con);
+/// // the atlas now contains the 6 letters of the greeting
+/// let atlas = font.atlas();
+/// ```
+pub struct TtfFont {
+    font: FontVec,
+    char_width: u32,
+    char_height: u32,
+    atlas: image::RgbaImage,
+    /// atlas index of each code point already rasterized
+    slots: HashMap<u32, u32>,
+    dirty: bool,
+}
+
+impl TtfFont {
+    /// parse a .ttf or .otf font. Glyphs will be rasterized to fit in cells of the given size in pixels.
+    pub fn from_bytes(data: Vec<u8>, char_width: u32, char_height: u32) -> Result<Self, String> {
+        let font = FontVec::try_from_vec(data).map_err(|e| e.to_string())?;
+        let mut ttf = Self {
+            font,
+            char_width: 0,
+            char_height: 0,
+            atlas: image::RgbaImage::new(1, 1),
+            slots: HashMap::new(),
+            dirty: true,
+        };
+        ttf.set_char_size(char_width, char_height);
+        Ok(ttf)
+    }
+    /// return the size of a cell in pixels
+    pub fn char_size(&self) -> (u32, u32) {
+        (self.char_width, self.char_height)
+    }
+    /// change the cell size. The atlas is cleared and the glyphs will be rasterized again at the new size,
+    /// which keeps the text crisp when the window is resized.
+    pub fn set_char_size(&mut self, char_width: u32, char_height: u32) {
+        let (char_width, char_height) = (char_width.max(1), char_height.max(1));
+        if (char_width, char_height) == (self.char_width, self.char_height) {
+            return;
+        }
+        self.char_width = char_width;
+        self.char_height = char_height;
+        let codes: Vec<(u32, u32)> = self.slots.drain().collect();
+        self.atlas = image::RgbaImage::new(ATLAS_COLUMNS * char_width, char_height);
+        // keep the same indices so that remapped consoles stay valid
+        for (code, slot) in codes {
+            self.rasterize(code, slot);
+            self.slots.insert(code, slot);
+        }
+        self.glyph(' ' as u32);
+        self.dirty = true;
+    }
+    /// the font image containing all the glyphs rasterized so far
+    pub fn atlas(&self) -> &image::RgbaImage {
+        &self.atlas
+    }
+    /// number of glyphs in the atlas
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+    /// return true if the atlas has changed since the last call
+    pub fn take_dirty(&mut self) -> bool {
+        std::mem::replace(&mut self.dirty, false)
+    }
+    /// return the atlas index of a code point, rasterizing it if needed
+    pub fn glyph(&mut self, code: u32) -> u32 {
+        if let Some(&slot) = self.slots.get(&code) {
+            return slot;
+        }
+        let slot = self.slots.len() as u32;
+        self.rasterize(code, slot);
+        self.slots.insert(code, slot);
+        self.dirty = true;
+        slot
+    }
+    /// rasterize the glyphs used by a console without modifying it
+    pub fn cache(&mut self, con: &Console) {
+        let pot_width = con.get_pot_width() as usize;
+        for y in 0..con.get_height() as usize {
+            for x in 0..con.get_width() as usize {
+                self.glyph(con.borrow_ascii()[x + y * pot_width]);
+            }
+        }
+    }
+    /// replace the code points of a console by their index in the atlas.
+    /// The modified area of the console (see [`Console::dirty_rect`]) is left unchanged
+    /// since a code point is always remapped to the same index.
+    pub fn remap(&mut self, con: &mut Console) {
+        let pot_width = con.get_pot_width() as usize;
+        let (width, height) = con.get_size();
+        let (ascii, _, _) = con.buffers_mut();
+        for y in 0..height as usize {
+            for x in 0..width as usize {
+                let off = x + y * pot_width;
+                ascii[off] = self.glyph(ascii[off]);
+            }
+        }
+    }
+    fn rasterize(&mut self, code: u32, slot: u32) {
+        let (cell_x, cell_y) = (
+            (slot % ATLAS_COLUMNS) * self.char_width,
+            (slot / ATLAS_COLUMNS) * self.char_height,
+        );
+        while cell_y + self.char_height > self.atlas.height() {
+            self.grow_atlas();
+        }
+        for y in cell_y..cell_y + self.char_height {
+            for x in cell_x..cell_x + self.char_width {
+                self.atlas.put_pixel(x, y, image::Rgba([255, 255, 255, 0]));
+            }
+        }
+        let ch = match char::from_u32(code) {
+            Some(ch) if !ch.is_control() && ch != ' ' => ch,
+            _ => return,
+        };
+        let glyph_id = self.font.glyph_id(ch);
+        // fit the line height in the cell, then shrink horizontally if the glyph is too wide
+        let mut scale = PxScale::from(self.char_height as f32);
+        let advance = self.font.as_scaled(scale).h_advance(glyph_id);
+        if advance > self.char_width as f32 {
+            scale.x *= self.char_width as f32 / advance;
+        }
+        let scaled = self.font.as_scaled(scale);
+        let left = (self.char_width as f32 - scaled.h_advance(glyph_id)).max(0.0) / 2.0;
+        let glyph = glyph_id.with_scale_and_position(
+            scale,
+            point(cell_x as f32 + left, cell_y as f32 + scaled.ascent()),
+        );
+        if let Some(outline) = self.font.outline_glyph(glyph) {
+            let bounds = outline.px_bounds();
+            let (char_width, char_height) = (self.char_width, self.char_height);
+            let atlas = &mut self.atlas;
+            outline.draw(|x, y, coverage| {
+                let px = bounds.min.x as i32 + x as i32;
+                let py = bounds.min.y as i32 + y as i32;
+                if px >= cell_x as i32
+                    && py >= cell_y as i32
+                    && px < (cell_x + char_width) as i32
+                    && py < (cell_y + char_height) as i32
+                {
+                    let alpha = (coverage.min(1.0) * 255.0).round() as u8;
+                    atlas.put_pixel(px as u32, py as u32, image::Rgba([255, 255, 255, alpha]));
+                }
+            });
+        }
+    }
+    fn grow_atlas(&mut self) {
+        let mut atlas = image::RgbaImage::new(self.atlas.width(), (self.atlas.height() * 2).max(1));
+        for (x, y, pixel) in self.atlas.enumerate_pixels() {
+            atlas.put_pixel(x, y, *pixel);
+        }
+        self.atlas = atlas;
+    }
+}
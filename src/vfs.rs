@@ -0,0 +1,333 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::{Mutex, OnceLock};
+
+use flate2::read::{DeflateDecoder, GzDecoder};
+
+/// A set of files stored in memory, either embedded in the binary or extracted from an archive.
+///
+/// Example
+/// ```
+/// use doryen_rs::Bundle;
+/// let mut bundle = Bundle::new();
+/// bundle.insert("fonts/terminal_8x8.png", include_bytes!("../www/terminal_8x8.png"));
+/// assert!(bundle.contains("fonts/terminal_8x8.png"));
+/// ```
+#[derive(Default, Clone)]
+pub struct Bundle {
+    files: HashMap<String, Cow<'static, [u8]>>,
+}
+
+impl Bundle {
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// extract all the files of a zip, tar or tar.gz archive.
+    /// Zip entries must be stored or deflated.
+    pub fn from_archive(data: &[u8]) -> Result<Self, String> {
+        if data.starts_with(b"PK\x03\x04") || data.starts_with(b"PK\x05\x06") {
+            read_zip(data)
+        } else if data.starts_with(&[0x1f, 0x8b]) {
+            let mut tar = Vec::new();
+            GzDecoder::new(data)
+                .read_to_end(&mut tar)
+                .map_err(|e| format!("Invalid gzip archive : {}", e))?;
+            read_tar(&tar)
+        } else {
+            read_tar(data)
+        }
+    }
+    /// add a file. Use `include_bytes!` to embed it in the binary.
+    pub fn insert<D: Into<Cow<'static, [u8]>>>(&mut self, path: &str, data: D) {
+        self.files.insert(normalize(path), data.into());
+    }
+    pub fn contains(&self, path: &str) -> bool {
+        self.files.contains_key(&normalize(path))
+    }
+    pub fn get(&self, path: &str) -> Option<&[u8]> {
+        self.files.get(&normalize(path)).map(|data| data.as_ref())
+    }
+    /// iterate over the files paths
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.files.keys().map(|path| path.as_str())
+    }
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+enum Source {
+    Dir(String),
+    Bundle(Bundle),
+}
+
+/// where a virtual path has been found
+pub(crate) enum Resolved {
+    /// a path to open with the platform filesystem (or an URL on the web)
+    File(String),
+    /// the content of a file from a bundle
+    Memory(Vec<u8>),
+}
+
+/// A virtual filesystem used by every file loader in doryen-rs ([`crate::FileLoader`],
+/// fonts, [`crate::Image`], [`crate::XpFile`]...).
+///
+/// Directories and bundles are mounted on a mount point (a path prefix, "" for the root).
+/// When a file is requested, the mounts are searched from the last one to the first one,
+/// so that a mod mounted after the base assets overrides them.
+/// If no mount contains the file, the path is opened as is.
+///
+/// On the web platform, files in directories are fetched asynchronously so their existence cannot
+/// be checked : the last directory mounted on a matching mount point is used.
+///
+/// The global virtual filesystem is accessed with [`with_vfs`].
+///
+/// Example
+/// ```
+/// use doryen_rs::{with_vfs, Bundle, FileLoader};
+/// let mut base = Bundle::new();
+/// base.insert("levels/level1.txt", b"base level".to_vec());
+/// let mut mod_files = Bundle::new();
+/// mod_files.insert("level1.txt", b"modded level".to_vec());
+/// with_vfs(|vfs| {
+///     vfs.mount_bundle("", base);
+///     vfs.mount_bundle("levels", mod_files);
+/// });
+/// let mut loader = FileLoader::new();
+/// let id = loader.load_file("levels/level1.txt").unwrap();
+/// assert_eq!(loader.check_file_ready(id), Ok(true));
+/// assert_eq!(loader.get_file_content(id), b"modded level");
+/// ```
+#[derive(Default)]
+pub struct Vfs {
+    mounts: Vec<(String, Source)>,
+}
+
+impl Vfs {
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// mount a directory of the platform filesystem (or an URL prefix on the web)
+    pub fn mount_dir(&mut self, mount_point: &str, dir: &str) {
+        self.mounts
+            .push((mount_prefix(mount_point), Source::Dir(mount_prefix(dir))));
+    }
+    /// mount the files of a bundle
+    pub fn mount_bundle(&mut self, mount_point: &str, bundle: Bundle) {
+        self.mounts
+            .push((mount_prefix(mount_point), Source::Bundle(bundle)));
+    }
+    /// extract a zip, tar or tar.gz archive and mount its files
+    pub fn mount_archive(&mut self, mount_point: &str, data: &[u8]) -> Result<(), String> {
+        self.mount_bundle(mount_point, Bundle::from_archive(data)?);
+        Ok(())
+    }
+    /// remove all the directories and bundles mounted on this mount point
+    pub fn unmount(&mut self, mount_point: &str) {
+        let prefix = mount_prefix(mount_point);
+        self.mounts.retain(|(mount, _)| *mount != prefix);
+    }
+    pub fn clear(&mut self) {
+        self.mounts.clear();
+    }
+    pub fn is_empty(&self) -> bool {
+        self.mounts.is_empty()
+    }
+    /// whether a mount contains this file.
+    /// Files in directories are always considered present on the web platform.
+    pub fn contains(&self, path: &str) -> bool {
+        let path = normalize(path);
+        self.find(&path).is_some()
+    }
+    pub(crate) fn resolve(&self, path: &str) -> Resolved {
+        let normalized = normalize(path);
+        match self.find(&normalized) {
+            Some((Source::Bundle(bundle), relative)) => {
+                Resolved::Memory(bundle.get(relative).unwrap_or_default().to_vec())
+            }
+            Some((Source::Dir(dir), relative)) => Resolved::File(format!("{}{}", dir, relative)),
+            None => Resolved::File(path.to_owned()),
+        }
+    }
+    /// the path in the platform filesystem of a file, or None if it's in a bundle
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn file_path(&self, path: &str) -> Option<String> {
+        match self.find(&normalize(path)) {
+            Some((Source::Bundle(_), _)) => None,
+            Some((Source::Dir(dir), relative)) => Some(format!("{}{}", dir, relative)),
+            None => Some(path.to_owned()),
+        }
+    }
+    fn find<'a>(&self, path: &'a str) -> Option<(&Source, &'a str)> {
+        self.mounts.iter().rev().find_map(|(mount, source)| {
+            let relative = path.strip_prefix(mount.as_str())?;
+            let found = match source {
+                Source::Bundle(bundle) => bundle.contains(relative),
+                Source::Dir(dir) => dir_contains(dir, relative),
+            };
+            if found {
+                Some((source, relative))
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// run a function on the global virtual filesystem
+pub fn with_vfs<R, F: FnOnce(&mut Vfs) -> R>(f: F) -> R {
+    static VFS: OnceLock<Mutex<Vfs>> = OnceLock::new();
+    let mut vfs = VFS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    f(&mut vfs)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn dir_contains(dir: &str, relative: &str) -> bool {
+    std::path::Path::new(&format!("{}{}", dir, relative)).is_file()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn dir_contains(_dir: &str, _relative: &str) -> bool {
+    true
+}
+
+/// convert a path to the form used as key : '/' separators, no leading "./" or '/'
+fn normalize(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let mut path = path.as_str();
+    loop {
+        if let Some(rest) = path.strip_prefix("./") {
+            path = rest;
+        } else if let Some(rest) = path.strip_prefix('/') {
+            path = rest;
+        } else {
+            return path.to_owned();
+        }
+    }
+}
+
+/// normalized mount point or directory, ending with '/' unless empty
+fn mount_prefix(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let mut prefix = if path.starts_with('/') {
+        // keep absolute directories
+        path
+    } else {
+        normalize(&path)
+    };
+    if !prefix.is_empty() && !prefix.ends_with('/') {
+        prefix.push('/');
+    }
+    prefix
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<usize, String> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]) as usize)
+        .ok_or_else(|| "Truncated zip archive".to_owned())
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<usize, String> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+        .ok_or_else(|| "Truncated zip archive".to_owned())
+}
+
+fn read_zip(data: &[u8]) -> Result<Bundle, String> {
+    // the end of central directory record is at the end of the file, before an optional comment
+    let eocd = (0..data.len().saturating_sub(21))
+        .rev()
+        .find(|&i| data[i..].starts_with(b"PK\x05\x06"))
+        .ok_or_else(|| "Zip end of central directory not found".to_owned())?;
+    let count = read_u16(data, eocd + 10)?;
+    let mut offset = read_u32(data, eocd + 16)?;
+    let mut bundle = Bundle::new();
+    for _ in 0..count {
+        if read_u32(data, offset)? != 0x0201_4b50 {
+            return Err("Invalid zip central directory".to_owned());
+        }
+        let method = read_u16(data, offset + 10)?;
+        let compressed_size = read_u32(data, offset + 20)?;
+        let size = read_u32(data, offset + 24)?;
+        let name_len = read_u16(data, offset + 28)?;
+        let extra_len = read_u16(data, offset + 30)?;
+        let comment_len = read_u16(data, offset + 32)?;
+        let header = read_u32(data, offset + 42)?;
+        let name = data
+            .get(offset + 46..offset + 46 + name_len)
+            .map(|name| String::from_utf8_lossy(name).into_owned())
+            .ok_or_else(|| "Truncated zip archive".to_owned())?;
+        offset += 46 + name_len + extra_len + comment_len;
+        if name.ends_with('/') {
+            continue;
+        }
+        let start = header + 30 + read_u16(data, header + 26)? + read_u16(data, header + 28)?;
+        let compressed = data
+            .get(start..start + compressed_size)
+            .ok_or_else(|| format!("Truncated zip entry {}", name))?;
+        let content = match method {
+            0 => compressed.to_vec(),
+            8 => {
+                // the size comes from the archive : it can't be trusted for the allocation
+                let mut content = Vec::with_capacity(size.min(data.len()));
+                // one more byte than expected to detect entries longer than their declared size
+                DeflateDecoder::new(compressed)
+                    .take(size as u64 + 1)
+                    .read_to_end(&mut content)
+                    .map_err(|e| format!("Invalid zip entry {} : {}", name, e))?;
+                if content.len() != size {
+                    return Err(format!("Invalid zip entry {} : wrong size", name));
+                }
+                content
+            }
+            _ => {
+                return Err(format!(
+                    "Unsupported compression method {} for zip entry {}",
+                    method, name
+                ))
+            }
+        };
+        bundle.insert(&name, content);
+    }
+    Ok(bundle)
+}
+
+fn read_tar(data: &[u8]) -> Result<Bundle, String> {
+    let mut bundle = Bundle::new();
+    let mut offset = 0;
+    while let Some(header) = data.get(offset..offset + 512) {
+        if header.iter().all(|&b| b == 0) {
+            break;
+        }
+        let field = |start: usize, len: usize| {
+            let field = &header[start..start + len];
+            let end = field.iter().position(|&b| b == 0).unwrap_or(len);
+            String::from_utf8_lossy(&field[..end]).into_owned()
+        };
+        let size = usize::from_str_radix(field(124, 12).trim(), 8)
+            .map_err(|_| "Invalid tar entry size".to_owned())?;
+        let mut name = field(0, 100);
+        if &header[257..262] == b"ustar" {
+            let prefix = field(345, 155);
+            if !prefix.is_empty() {
+                name = format!("{}/{}", prefix, name);
+            }
+        }
+        let content = data
+            .get(offset + 512..offset + 512 + size)
+            .ok_or_else(|| format!("Truncated tar entry {}", name))?;
+        // regular files only
+        if matches!(header[156], b'0' | 0) {
+            bundle.insert(&name, content.to_vec());
+        }
+        offset += 512 + size.div_ceil(512) * 512;
+    }
+    Ok(bundle)
+}
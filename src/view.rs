@@ -0,0 +1,498 @@
+use crate::cell::Cell;
+use crate::color::{BlendMode, Color};
+use crate::console::{Console, TextAlign};
+use crate::draw::DrawTarget;
+use crate::frame::FrameStyle;
+
+/// The part of a console where the drawing methods work
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Viewport {
+    /// position of the top-left corner on the console
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    /// visible part in local coordinates : min x, min y, max x, max y (exclusive)
+    pub clip: (i32, i32, i32, i32),
+}
+
+impl Viewport {
+    pub fn full(width: u32, height: u32) -> Self {
+        Self {
+            x: 0,
+            y: 0,
+            width,
+            height,
+            clip: (0, 0, width as i32, height as i32),
+        }
+    }
+    /// a rectangle of this viewport, in its local coordinates
+    fn sub(&self, x: i32, y: i32, width: u32, height: u32) -> Self {
+        let (x0, y0, x1, y1) = self.clip;
+        Self {
+            x: self.x + x,
+            y: self.y + y,
+            width,
+            height,
+            clip: (
+                (x0 - x).max(0),
+                (y0 - y).max(0),
+                (x1 - x).min(width as i32),
+                (y1 - y).min(height as i32),
+            ),
+        }
+    }
+}
+
+/// A rectangular part of a console with its own coordinates : (0, 0) is the top-left corner of the view.
+/// Everything drawn outside of the view is clipped. Views can be nested to lay out a user interface.
+///
+/// Example
+/// ```
+/// use doryen_rs::{Console, TextAlign};
+/// let mut con = Console::new(80, 25);
+/// let mut panel = con.view_mut(60, 2, 10, 5);
+/// panel.print(0, 0, "Inventories", TextAlign::Left, None, None);
+/// let mut slot = panel.view_mut(1, 2, 3, 1);
+/// slot.cell(0, 0, Some('@' as u16), None, None);
+/// assert_eq!(con.get_ascii(60, 2), Some('I' as u16));
+/// assert_eq!(con.get_ascii(69, 2), Some('e' as u16));
+/// assert_eq!(con.get_ascii(70, 2), Some(' ' as u16)); // clipped
+/// assert_eq!(con.get_ascii(61, 4), Some('@' as u16));
+/// ```
+pub struct ConsoleView<'a> {
+    con: &'a mut Console,
+    viewport: Viewport,
+}
+
+/// puts back the console's viewport when dropped, even if the drawing code panics
+struct RestoreViewport<'a> {
+    con: &'a mut Console,
+    viewport: Viewport,
+}
+
+impl Drop for RestoreViewport<'_> {
+    fn drop(&mut self) {
+        self.con.set_viewport(self.viewport);
+    }
+}
+
+impl Console {
+    /// a view on a rectangle of this console. See [`ConsoleView`]
+    pub fn view_mut(&mut self, x: i32, y: i32, w: u32, h: u32) -> ConsoleView<'_> {
+        let viewport = self.viewport().sub(x, y, w, h);
+        ConsoleView {
+            con: self,
+            viewport,
+        }
+    }
+}
+
+impl<'a> ConsoleView<'a> {
+    /// a view on a rectangle of this view, in local coordinates
+    pub fn view_mut(&mut self, x: i32, y: i32, w: u32, h: u32) -> ConsoleView<'_> {
+        ConsoleView {
+            viewport: self.viewport.sub(x, y, w, h),
+            con: self.con,
+        }
+    }
+    /// position of the view's top-left corner on the console
+    pub fn get_position(&self) -> (i32, i32) {
+        (self.viewport.x, self.viewport.y)
+    }
+    pub fn get_width(&self) -> u32 {
+        self.viewport.width
+    }
+    pub fn get_height(&self) -> u32 {
+        self.viewport.height
+    }
+    pub fn get_size(&self) -> (u32, u32) {
+        (self.viewport.width, self.viewport.height)
+    }
+    /// the console this view draws on
+    pub fn console(&self) -> &Console {
+        self.con
+    }
+    /// run a console method with the view's coordinates and clipping
+    fn draw<R, F: FnOnce(&mut Console) -> R>(&mut self, f: F) -> R {
+        let viewport = self.con.viewport();
+        self.con.set_viewport(self.viewport);
+        let guard = RestoreViewport {
+            con: &mut *self.con,
+            viewport,
+        };
+        f(guard.con)
+    }
+    /// console coordinates of a cell of the view, None if it's clipped
+    fn console_coords(&self, x: i32, y: i32) -> Option<(i32, i32)> {
+        let (x0, y0, x1, y1) = self.viewport.clip;
+        if x >= x0 && x < x1 && y >= y0 && y < y1 {
+            Some((x + self.viewport.x, y + self.viewport.y))
+        } else {
+            None
+        }
+    }
+    /// see [`Console::get_back`]
+    pub fn get_back(&self, x: i32, y: i32) -> Option<Color> {
+        let (x, y) = self.console_coords(x, y)?;
+        self.con.get_back(x, y)
+    }
+    /// see [`Console::get_fore`]
+    pub fn get_fore(&self, x: i32, y: i32) -> Option<Color> {
+        let (x, y) = self.console_coords(x, y)?;
+        self.con.get_fore(x, y)
+    }
+    /// see [`Console::get_ascii`]
+    pub fn get_ascii(&self, x: i32, y: i32) -> Option<u16> {
+        let (x, y) = self.console_coords(x, y)?;
+        self.con.get_ascii(x, y)
+    }
+    /// see [`Console::get_glyph`]
+    pub fn get_glyph(&self, x: i32, y: i32) -> Option<u32> {
+        let (x, y) = self.console_coords(x, y)?;
+        self.con.get_glyph(x, y)
+    }
+    /// see [`Console::ascii`]
+    pub fn ascii(&mut self, x: i32, y: i32, ascii: u16) {
+        self.draw(|con| con.ascii(x, y, ascii));
+    }
+    /// see [`Console::glyph`]
+    pub fn glyph(&mut self, x: i32, y: i32, glyph: u32) {
+        self.draw(|con| con.glyph(x, y, glyph));
+    }
+    /// see [`Console::fore`]
+    pub fn fore(&mut self, x: i32, y: i32, col: Color) {
+        self.draw(|con| con.fore(x, y, col));
+    }
+    /// see [`Console::back`]
+    pub fn back(&mut self, x: i32, y: i32, col: Color) {
+        self.draw(|con| con.back(x, y, col));
+    }
+    /// see [`Console::cell`]
+    pub fn cell(
+        &mut self,
+        x: i32,
+        y: i32,
+        ascii: Option<u16>,
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) {
+        self.draw(|con| con.cell(x, y, ascii, fore, back));
+    }
+    /// see [`Console::cell_blend`]
+    pub fn cell_blend(
+        &mut self,
+        x: i32,
+        y: i32,
+        ascii: Option<u16>,
+        fore: Option<Color>,
+        back: Option<Color>,
+        mode: BlendMode,
+    ) {
+        self.draw(|con| con.cell_blend(x, y, ascii, fore, back, mode));
+    }
+    /// fill the whole view with values
+    pub fn clear(&mut self, fore: Option<Color>, back: Option<Color>, fillchar: Option<u16>) {
+        self.draw(|con| con.clear(fore, back, fillchar));
+    }
+    /// see [`Console::print`]
+    pub fn print(
+        &mut self,
+        x: i32,
+        y: i32,
+        text: &str,
+        align: TextAlign,
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) {
+        self.draw(|con| con.print(x, y, text, align, fore, back));
+    }
+    /// see [`Console::print_color`]
+    pub fn print_color(
+        &mut self,
+        x: i32,
+        y: i32,
+        text: &str,
+        align: TextAlign,
+        back: Option<Color>,
+    ) {
+        self.draw(|con| con.print_color(x, y, text, align, back));
+    }
+    /// see [`Console::print_rect`]. With `h` = 0, the height is limited by the view's border.
+    #[allow(clippy::too_many_arguments)]
+    pub fn print_rect(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        text: &str,
+        align: TextAlign,
+        back: Option<Color>,
+    ) -> u32 {
+        self.draw(|con| con.print_rect(x, y, w, h, text, align, back))
+    }
+    /// see [`Console::rectangle`]
+    #[allow(clippy::too_many_arguments)]
+    pub fn rectangle(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        fore: Option<Color>,
+        back: Option<Color>,
+        fill: Option<u16>,
+    ) {
+        self.draw(|con| con.rectangle(x, y, w, h, fore, back, fill));
+    }
+    /// see [`Console::frame`]
+    #[allow(clippy::too_many_arguments)]
+    pub fn frame(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        style: &FrameStyle,
+        fore: Option<Color>,
+        back: Option<Color>,
+        fill: Option<u16>,
+    ) {
+        self.draw(|con| con.frame(x, y, w, h, style, fore, back, fill));
+    }
+    /// see [`Console::area`]
+    #[allow(clippy::too_many_arguments)]
+    pub fn area(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        fore: Option<Color>,
+        back: Option<Color>,
+        fillchar: Option<u16>,
+    ) {
+        self.draw(|con| con.area(x, y, w, h, fore, back, fillchar));
+    }
+    /// see [`Console::area_blend`]
+    #[allow(clippy::too_many_arguments)]
+    pub fn area_blend(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        fore: Option<Color>,
+        back: Option<Color>,
+        mode: BlendMode,
+    ) {
+        self.draw(|con| con.area_blend(x, y, w, h, fore, back, mode));
+    }
+    /// see [`Console::line`]
+    #[allow(clippy::too_many_arguments)]
+    pub fn line(
+        &mut self,
+        x1: i32,
+        y1: i32,
+        x2: i32,
+        y2: i32,
+        ascii: Option<u16>,
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) {
+        self.draw(|con| con.line(x1, y1, x2, y2, ascii, fore, back));
+    }
+    /// see [`Console::circle`]
+    pub fn circle(
+        &mut self,
+        x: i32,
+        y: i32,
+        radius: u32,
+        ascii: Option<u16>,
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) {
+        self.draw(|con| con.circle(x, y, radius, ascii, fore, back));
+    }
+    /// see [`Console::fill_circle`]
+    pub fn fill_circle(
+        &mut self,
+        x: i32,
+        y: i32,
+        radius: u32,
+        ascii: Option<u16>,
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) {
+        self.draw(|con| con.fill_circle(x, y, radius, ascii, fore, back));
+    }
+    /// see [`Console::ellipse`]
+    #[allow(clippy::too_many_arguments)]
+    pub fn ellipse(
+        &mut self,
+        x: i32,
+        y: i32,
+        rx: u32,
+        ry: u32,
+        ascii: Option<u16>,
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) {
+        self.draw(|con| con.ellipse(x, y, rx, ry, ascii, fore, back));
+    }
+    /// see [`Console::fill_ellipse`]
+    #[allow(clippy::too_many_arguments)]
+    pub fn fill_ellipse(
+        &mut self,
+        x: i32,
+        y: i32,
+        rx: u32,
+        ry: u32,
+        ascii: Option<u16>,
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) {
+        self.draw(|con| con.fill_ellipse(x, y, rx, ry, ascii, fore, back));
+    }
+    /// see [`Console::polygon`]
+    pub fn polygon(
+        &mut self,
+        points: &[(i32, i32)],
+        ascii: Option<u16>,
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) {
+        self.draw(|con| con.polygon(points, ascii, fore, back));
+    }
+    /// see [`Console::fill_polygon`]
+    pub fn fill_polygon(
+        &mut self,
+        points: &[(i32, i32)],
+        ascii: Option<u16>,
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) {
+        self.draw(|con| con.fill_polygon(points, ascii, fore, back));
+    }
+    /// see [`Console::flood_fill`]. The filled region doesn't extend outside of the view.
+    pub fn flood_fill<F: Fn(u32, Color, Color) -> bool>(
+        &mut self,
+        x: i32,
+        y: i32,
+        matches: F,
+        ascii: Option<u16>,
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) -> u32 {
+        self.draw(|con| con.flood_fill(x, y, matches, ascii, fore, back))
+    }
+    /// draw a console on this view. See [`Console::blit`]
+    pub fn blit_from(
+        &mut self,
+        source: &Console,
+        x: i32,
+        y: i32,
+        fore_alpha: f32,
+        back_alpha: f32,
+        key_color: Option<Color>,
+    ) {
+        self.draw(|con| source.blit(x, y, con, fore_alpha, back_alpha, key_color));
+    }
+    /// draw a console on this view, combining the colors with a [`BlendMode`]. See [`Console::blit_blend`]
+    #[allow(clippy::too_many_arguments)]
+    pub fn blit_blend_from(
+        &mut self,
+        source: &Console,
+        x: i32,
+        y: i32,
+        fore_alpha: f32,
+        back_alpha: f32,
+        key_color: Option<Color>,
+        mode: BlendMode,
+    ) {
+        self.draw(|con| source.blit_blend(x, y, con, fore_alpha, back_alpha, key_color, mode));
+    }
+}
+
+impl<'a> DrawTarget for ConsoleView<'a> {
+    fn get_size(&self) -> (u32, u32) {
+        ConsoleView::get_size(self)
+    }
+    fn get_cell(&self, x: i32, y: i32) -> Option<Cell> {
+        let (x, y) = self.console_coords(x, y)?;
+        self.con.get_cell(x, y)
+    }
+    fn put_cell(
+        &mut self,
+        x: i32,
+        y: i32,
+        glyph: Option<u32>,
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) {
+        self.draw(|con| con.put_cell(x, y, glyph, fore, back));
+    }
+    fn map_char(&self, ch: char) -> u32 {
+        self.con.map_char(ch)
+    }
+    fn named_color(&self, name: &str) -> Option<Color> {
+        self.con.named_color(name)
+    }
+    fn named_glyph(&self, name: &str) -> Option<u32> {
+        self.con.named_glyph(name)
+    }
+    fn print(
+        &mut self,
+        x: i32,
+        y: i32,
+        text: &str,
+        align: TextAlign,
+        fore: Option<Color>,
+        back: Option<Color>,
+    ) {
+        ConsoleView::print(self, x, y, text, align, fore, back);
+    }
+    fn print_color(&mut self, x: i32, y: i32, text: &str, align: TextAlign, back: Option<Color>) {
+        ConsoleView::print_color(self, x, y, text, align, back);
+    }
+    fn print_rect(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        text: &str,
+        align: TextAlign,
+        back: Option<Color>,
+    ) -> u32 {
+        ConsoleView::print_rect(self, x, y, w, h, text, align, back)
+    }
+    fn rectangle(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        fore: Option<Color>,
+        back: Option<Color>,
+        fill: Option<u16>,
+    ) {
+        ConsoleView::rectangle(self, x, y, w, h, fore, back, fill);
+    }
+    fn area(
+        &mut self,
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+        fore: Option<Color>,
+        back: Option<Color>,
+        fillchar: Option<u16>,
+    ) {
+        ConsoleView::area(self, x, y, w, h, fore, back, fillchar);
+    }
+    fn clear(&mut self, fore: Option<Color>, back: Option<Color>, fillchar: Option<u16>) {
+        ConsoleView::clear(self, fore, back, fillchar);
+    }
+}
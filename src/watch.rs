@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+
+use crate::vfs::with_vfs;
+
+/// seconds between two checks of the files modification times
+const POLL_INTERVAL: f64 = 0.5;
+
+/// a watched file, with its last modification time and a version number incremented each time it changes
+struct WatchedFile {
+    mtime: Option<SystemTime>,
+    version: u64,
+    /// number of watchers, images and xp files using this file. The file is forgotten when it drops to 0.
+    users: usize,
+}
+
+/// The files watched for hot reloading. This is shared by all the apps, like the [`crate::Vfs`],
+/// because images and xp files don't know which app they are used by.
+#[derive(Default)]
+struct Registry {
+    files: HashMap<String, WatchedFile>,
+    /// number of apps with hot reloading enabled. Files are only watched while there is one.
+    watchers: usize,
+}
+
+fn with_registry<R, F: FnOnce(&mut Registry) -> R>(f: F) -> R {
+    static REGISTRY: OnceLock<Mutex<Registry>> = OnceLock::new();
+    let mut registry = REGISTRY
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    f(&mut registry)
+}
+
+/// start watching a file if an app has hot reloading enabled. Files from bundles are never modified.
+/// Returns false if the file is not watched. Else [`unwatch_file`] must be called once it's not used anymore.
+pub(crate) fn watch_file(path: &str) -> bool {
+    if with_registry(|registry| registry.watchers) == 0 {
+        return false;
+    }
+    let mtime = modification_time(path);
+    with_registry(|registry| {
+        let file = registry
+            .files
+            .entry(path.to_owned())
+            .or_insert(WatchedFile {
+                mtime,
+                version: 0,
+                users: 0,
+            });
+        file.users += 1;
+    });
+    true
+}
+
+/// stop using a file registered with [`watch_file`]
+pub(crate) fn unwatch_file(path: &str) {
+    with_registry(|registry| {
+        if let Some(file) = registry.files.get_mut(path) {
+            file.users -= 1;
+            if file.users == 0 {
+                registry.files.remove(path);
+            }
+        }
+    });
+}
+
+/// number of times a watched file has changed
+pub(crate) fn file_version(path: &str) -> u64 {
+    with_registry(|registry| registry.files.get(path).map_or(0, |file| file.version))
+}
+
+/// The hot reloading state of an app : when it last checked the files and which changes it has seen.
+pub(crate) struct Watcher {
+    next_poll: f64,
+    /// version of each file the last time this app checked it
+    versions: HashMap<String, u64>,
+    /// the files watched by this app (fonts and [`crate::DoryenApi::watch_file`])
+    paths: Vec<String>,
+}
+
+impl Watcher {
+    pub(crate) fn new() -> Self {
+        with_registry(|registry| registry.watchers += 1);
+        Self {
+            next_poll: 0.0,
+            versions: HashMap::new(),
+            paths: Vec::new(),
+        }
+    }
+    /// watch a file until this watcher is dropped or [`Watcher::unwatch`] is called
+    pub(crate) fn watch(&mut self, path: &str) {
+        if watch_file(path) {
+            self.versions
+                .entry(path.to_owned())
+                .or_insert_with(|| file_version(path));
+            self.paths.push(path.to_owned());
+        }
+    }
+    pub(crate) fn unwatch(&mut self, path: &str) {
+        if let Some(pos) = self.paths.iter().position(|p| p == path) {
+            self.paths.swap_remove(pos);
+            unwatch_file(path);
+        }
+    }
+    /// check the modification time of the watched files if the poll interval has elapsed since the last check.
+    /// Returns the paths of the files that have changed since this watcher last checked them.
+    pub(crate) fn poll(&mut self, time: f64) -> Vec<String> {
+        if time < self.next_poll {
+            return Vec::new();
+        }
+        self.next_poll = time + POLL_INTERVAL;
+        let paths: Vec<String> = with_registry(|registry| {
+            // forget the removed files and start from the current version of the new ones
+            self.versions
+                .retain(|path, _| registry.files.contains_key(path));
+            for (path, file) in registry.files.iter() {
+                self.versions.entry(path.clone()).or_insert(file.version);
+            }
+            registry.files.keys().cloned().collect()
+        });
+        let mtimes: Vec<(String, Option<SystemTime>)> = paths
+            .into_iter()
+            .map(|path| {
+                let mtime = modification_time(&path);
+                (path, mtime)
+            })
+            .collect();
+        with_registry(|registry| {
+            let mut changed = Vec::new();
+            for (path, mtime) in mtimes {
+                if let Some(file) = registry.files.get_mut(&path) {
+                    if mtime.is_some() && file.mtime != mtime {
+                        file.mtime = mtime;
+                        file.version += 1;
+                    }
+                    // the change may have been detected by another app
+                    if let Some(version) = self.versions.get_mut(&path) {
+                        if *version != file.version {
+                            *version = file.version;
+                            changed.push(path);
+                        }
+                    }
+                }
+            }
+            changed
+        })
+    }
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        for path in self.paths.drain(..) {
+            unwatch_file(&path);
+        }
+        with_registry(|registry| registry.watchers -= 1);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn modification_time(path: &str) -> Option<SystemTime> {
+    let real_path = with_vfs(|vfs| vfs.file_path(path))?;
+    std::fs::metadata(real_path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn modification_time(_path: &str) -> Option<SystemTime> {
+    None
+}
@@ -0,0 +1,227 @@
+use std::io::{Read, Write};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+use crate::color::Color;
+use crate::console::Console;
+use crate::error::LoadError;
+use crate::file::FileLoader;
+use crate::serialize::ByteReader;
+use crate::watch::{file_version, unwatch_file, watch_file};
+
+/// The background color used by REXPaint for transparent cells.
+/// Use it as key color in [`Console::blit`] to draw a layer over another one.
+pub const XP_TRANSPARENT: Color = (255, 0, 255, 255);
+
+const XP_VERSION: i32 = -1;
+
+impl Console {
+    /// Read the content of a REXPaint .xp file (gzip compressed).
+    /// Each layer of the file is returned as a separate console.
+    /// Transparent cells use the [`XP_TRANSPARENT`] background color.
+    ///
+    /// Example
+    /// ```
+    /// use doryen_rs::Console;
+    /// let mut con = Console::new(3, 2);
+    /// con.cell(1, 1, Some('@' as u16), Some((255, 255, 0, 255)), Some((0, 0, 64, 255)));
+    /// let layers = Console::from_xp(&con.to_xp()).unwrap();
+    /// assert_eq!(layers.len(), 1);
+    /// assert_eq!(layers[0].get_size(), (3, 2));
+    /// assert_eq!(layers[0].get_ascii(1, 1), Some('@' as u16));
+    /// assert_eq!(layers[0].get_back(1, 1), Some((0, 0, 64, 255)));
+    /// ```
+    pub fn from_xp(buf: &[u8]) -> Result<Vec<Console>, String> {
+        let mut data = Vec::new();
+        GzDecoder::new(buf)
+            .read_to_end(&mut data)
+            .map_err(|e| format!("Could not decompress xp file : {}", e))?;
+        let mut reader = ByteReader::new(&data, "xp file");
+        let mut layer_count = reader.read_i32()?;
+        if layer_count < 0 {
+            // newer files start with a version number
+            layer_count = reader.read_i32()?;
+        }
+        let mut layers = Vec::new();
+        for _ in 0..layer_count {
+            let width = reader.read_i32()?;
+            let height = reader.read_i32()?;
+            if width <= 0 || height <= 0 {
+                return Err(format!("Invalid xp layer size {} x {}", width, height));
+            }
+            // each cell uses 10 bytes : glyph (4), foreground (3) and background (3)
+            let size = (width as usize)
+                .checked_mul(height as usize)
+                .and_then(|cells| cells.checked_mul(10));
+            if size.is_none_or(|size| size > reader.remaining()) {
+                return Err(format!(
+                    "Invalid xp layer size {} x {} : unexpected end of xp file",
+                    width, height
+                ));
+            }
+            let mut con = Console::new(width as u32, height as u32);
+            let pot_width = con.get_pot_width() as usize;
+            // cells are stored column by column
+            for x in 0..width {
+                for y in 0..height {
+                    let ascii = reader.read_u32()?;
+                    let fore = reader.read_rgb()?;
+                    let back = reader.read_rgb()?;
+                    con.unsafe_fore(x, y, fore);
+                    con.unsafe_back(x, y, back);
+                    con.borrow_mut_ascii()[x as usize + y as usize * pot_width] = ascii;
+                }
+            }
+            layers.push(con);
+        }
+        Ok(layers)
+    }
+    /// Read the content of a REXPaint .xp file and merge all its layers into a single console.
+    /// Transparent cells from upper layers are ignored.
+    pub fn from_xp_merged(buf: &[u8]) -> Result<Console, String> {
+        let mut layers = Console::from_xp(buf)?.into_iter();
+        let mut con = layers.next().ok_or("xp file has no layer")?;
+        for layer in layers {
+            layer.blit(0, 0, &mut con, 1.0, 1.0, Some(XP_TRANSPARENT));
+        }
+        Ok(con)
+    }
+    /// Export this console as a single layer REXPaint .xp file
+    pub fn to_xp(&self) -> Vec<u8> {
+        Console::layers_to_xp(&[self])
+    }
+    /// Export several consoles as layers of a REXPaint .xp file.
+    /// Use the [`XP_TRANSPARENT`] background color for transparent cells.
+    pub fn layers_to_xp(layers: &[&Console]) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&XP_VERSION.to_le_bytes());
+        data.extend_from_slice(&(layers.len() as i32).to_le_bytes());
+        for con in layers.iter() {
+            data.extend_from_slice(&(con.get_width() as i32).to_le_bytes());
+            data.extend_from_slice(&(con.get_height() as i32).to_le_bytes());
+            let pot_width = con.get_pot_width() as usize;
+            for x in 0..con.get_width() as usize {
+                for y in 0..con.get_height() as usize {
+                    let off = x + y * pot_width;
+                    let fore = con.borrow_foreground()[off];
+                    let back = con.borrow_background()[off];
+                    data.extend_from_slice(&con.borrow_ascii()[off].to_le_bytes());
+                    data.extend_from_slice(&[fore.0, fore.1, fore.2, back.0, back.1, back.2]);
+                }
+            }
+        }
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(&data)
+            .and_then(|_| encoder.finish())
+            .expect("Failed to compress xp file")
+    }
+}
+
+/// An easy way to load REXPaint .xp files.
+/// On the web platform, file loading is asynchronous.
+pub struct XpFile {
+    file_loader: FileLoader,
+    layers: Option<Vec<Console>>,
+    path: String,
+    /// id of the file request in the file loader
+    file_id: usize,
+    error: Option<LoadError>,
+    /// version of the file when it was loaded, to detect hot reloading
+    version: u64,
+    /// whether the file is watched for hot reloading
+    watched: bool,
+}
+
+impl XpFile {
+    /// Create a xp file and start loading it.
+    pub fn new(file_path: &str) -> Self {
+        let mut file_loader = FileLoader::new();
+        let (file_id, error) = match file_loader.load_file(file_path) {
+            Ok(file_id) => (file_id, None),
+            Err(e) => (0, Some(e)),
+        };
+        let watched = watch_file(file_path);
+        Self {
+            file_loader,
+            layers: None,
+            path: file_path.to_owned(),
+            file_id,
+            error,
+            version: file_version(file_path),
+            watched,
+        }
+    }
+    /// Check if the file has been loaded.
+    /// Since there's no background thread doing the work for you, you have to call this method for the file to actually load.
+    /// Returns false if the file could not be read or is not a valid xp file, see [`XpFile::error`].
+    pub fn try_load(&mut self) -> bool {
+        let version = file_version(&self.path);
+        if version != self.version {
+            self.version = version;
+            self.layers = None;
+            match self.file_loader.load_file(&self.path) {
+                Ok(file_id) => {
+                    self.file_id = file_id;
+                    self.error = None;
+                }
+                Err(e) => self.error = Some(e),
+            }
+        }
+        if self.layers.is_some() {
+            return true;
+        }
+        if self.error.is_some() {
+            return false;
+        }
+        match self.file_loader.check_file_ready(self.file_id) {
+            Ok(true) => {
+                let buf = self.file_loader.get_file_content(self.file_id);
+                match Console::from_xp(&buf) {
+                    Ok(layers) => self.layers = Some(layers),
+                    Err(message) => {
+                        self.error = Some(LoadError::InvalidContent {
+                            path: self.path.clone(),
+                            message,
+                        })
+                    }
+                }
+                self.layers.is_some()
+            }
+            Ok(false) => false,
+            Err(e) => {
+                self.error = Some(e);
+                false
+            }
+        }
+    }
+    /// the error that prevented the file from being loaded, if any
+    pub fn error(&self) -> Option<&LoadError> {
+        self.error.as_ref()
+    }
+    /// Return the layers of the file, or None if it has not been loaded yet.
+    pub fn layers(&mut self) -> Option<&[Console]> {
+        if self.try_load() {
+            return self.layers.as_deref();
+        }
+        None
+    }
+    /// Blit all the layers on a console, ignoring the transparent cells.
+    pub fn blit(&mut self, con: &mut Console, x: i32, y: i32) {
+        if let Some(layers) = self.layers() {
+            for layer in layers {
+                layer.blit(x, y, con, 1.0, 1.0, Some(XP_TRANSPARENT));
+            }
+        }
+    }
+}
+
+impl Drop for XpFile {
+    fn drop(&mut self) {
+        if self.watched {
+            unwatch_file(&self.path);
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use doryen_rs::Console;
+
+#[test]
+fn cursor_far_below_is_ignored() {
+    let con = Console::from_ansi(b"A\x1b[999999999HB", Some(10));
+    assert_eq!(con.get_size(), (10, 1));
+    assert_eq!(con.get_ascii(0, 0), Some('A' as u16));
+}
+
+#[test]
+fn repeated_cursor_moves_dont_overflow() {
+    let mut data = b"A".to_vec();
+    for _ in 0..3 {
+        data.extend_from_slice(b"\x1b[4294967295B\x1b[4294967295C");
+    }
+    data.extend_from_slice(b"B");
+    let con = Console::from_ansi(&data, Some(10));
+    assert_eq!(con.get_size(), (10, 1));
+}
+
+#[test]
+fn cursor_moves_back_up_after_the_limit() {
+    let con = Console::from_ansi(b"\x1b[999999999B\x1b[999999999AX", Some(10));
+    assert_eq!(con.get_ascii(0, 0), Some('X' as u16));
+}
+
+/// a SAUCE record for a character file with the given width
+fn sauce(width: u16) -> Vec<u8> {
+    let mut record = vec![0u8; 128];
+    record[..7].copy_from_slice(b"SAUCE00");
+    record[94] = 1;
+    record[96..98].copy_from_slice(&width.to_le_bytes());
+    record
+}
+
+#[test]
+fn sauce_width_is_capped() {
+    let mut data = b"A".to_vec();
+    data.extend_from_slice(&sauce(0xffff));
+    let con = Console::from_ansi(&data, None);
+    assert_eq!(con.get_size(), (1000, 1));
+    assert_eq!(con.get_ascii(0, 0), Some('A' as u16));
+}
+
+#[test]
+fn requested_width_is_capped() {
+    let con = Console::from_ansi(b"A", Some(u32::MAX));
+    assert_eq!(con.get_size(), (1000, 1));
+}
+
+#[test]
+fn huge_sauce_width_with_many_lines_is_bounded() {
+    let mut data = b"\x1b[9999;1HA".to_vec();
+    data.extend_from_slice(&sauce(0xffff));
+    let con = Console::from_ansi(&data, None);
+    assert_eq!(con.get_size(), (1000, 9999));
+    assert_eq!(con.get_ascii(0, 9998), Some('A' as u16));
+}
+
+#[test]
+fn dump_of_a_huge_region_is_clipped() {
+    let con = Console::from_ansi(b"AB", Some(2));
+    assert_eq!(
+        con.to_ansi(1, 0, u32::MAX / 2, u32::MAX / 2)
+            .matches('B')
+            .count(),
+        1
+    );
+}
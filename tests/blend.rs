@@ -0,0 +1,89 @@
+use doryen_rs::{BlendMode, Console, Layer, LayerStack};
+
+fn source() -> Console {
+    let mut con = Console::new(3, 1);
+    con.cell(
+        0,
+        0,
+        Some('@' as u16),
+        Some((255, 255, 0, 255)),
+        Some((0, 0, 200, 255)),
+    );
+    con.cell(1, 0, Some(' ' as u16), None, Some((200, 0, 0, 255)));
+    con.cell(
+        2,
+        0,
+        Some('#' as u16),
+        Some((0, 255, 0, 128)),
+        Some((0, 0, 0, 64)),
+    );
+    con
+}
+
+fn background() -> Console {
+    let mut con = Console::new(3, 1);
+    con.clear(
+        Some((90, 90, 90, 255)),
+        Some((40, 80, 120, 255)),
+        Some('.' as u16),
+    );
+    con
+}
+
+#[test]
+fn alpha_blit_matches_alpha_layer() {
+    for opacity in [0.25, 0.5, 1.0] {
+        let mut blitted = background();
+        source().blit_blend(0, 0, &mut blitted, opacity, opacity, None, BlendMode::Alpha);
+
+        let mut layers = LayerStack::new();
+        let layer = layers.push("layer", Layer::new(3, 1));
+        layer.console = source();
+        layer.opacity = opacity;
+        let mut composited = Console::new(3, 1);
+        layers.composite(&background(), &mut composited);
+
+        for x in 0..3 {
+            assert_eq!(blitted.get_cell(x, 0), composited.get_cell(x, 0));
+        }
+    }
+}
+
+#[test]
+fn region_blit_only_touches_the_region() {
+    let src = source();
+    let mut dst = background();
+    src.blit_ex_blend(
+        1,
+        0,
+        2,
+        1,
+        &mut dst,
+        0,
+        0,
+        1.0,
+        1.0,
+        None,
+        BlendMode::Replace,
+    );
+    assert_eq!(dst.get_back(0, 0), Some((200, 0, 0, 255)));
+    assert_eq!(dst.get_ascii(1, 0), Some('#' as u16));
+    assert_eq!(dst.get_back(2, 0), Some((40, 80, 120, 255)));
+    // the region is clipped to the source console
+    let mut dst = background();
+    src.blit_ex_blend(
+        2,
+        0,
+        5,
+        5,
+        &mut dst,
+        0,
+        0,
+        1.0,
+        1.0,
+        None,
+        BlendMode::Replace,
+    );
+    assert_eq!(dst.get_ascii(0, 0), Some('#' as u16));
+    assert_eq!(dst.get_ascii(1, 0), Some('.' as u16));
+}
@@ -0,0 +1,53 @@
+use doryen_rs::{Cell, Color, Console, DrawTarget, Layer};
+
+/// a target counting the cells written
+struct Counter {
+    written: usize,
+}
+
+impl DrawTarget for Counter {
+    fn get_size(&self) -> (u32, u32) {
+        (4, 3)
+    }
+    fn get_cell(&self, _x: i32, _y: i32) -> Option<Cell> {
+        None
+    }
+    fn put_cell(
+        &mut self,
+        _x: i32,
+        _y: i32,
+        _glyph: Option<u32>,
+        _fore: Option<Color>,
+        _back: Option<Color>,
+    ) {
+        self.written += 1;
+    }
+}
+
+#[test]
+fn huge_area_is_clipped_to_the_target() {
+    let mut target = Counter { written: 0 };
+    target.area(
+        1,
+        1,
+        i32::MAX as u32,
+        i32::MAX as u32,
+        None,
+        None,
+        Some(b'#' as u16),
+    );
+    assert_eq!(target.written, 3 * 2);
+}
+
+#[test]
+fn layer_rectangle_without_fill_keeps_the_interior_like_console_rectangle() {
+    const RED: Color = (255, 0, 0, 255);
+    let mut layer = Layer::new(6, 6);
+    let mut con = Console::new(6, 6);
+    con.clear(None, Some((0, 0, 0, 0)), Some(' ' as u16));
+    layer.rectangle(0, 0, 6, 6, Some(RED), Some(RED), None);
+    con.rectangle(0, 0, 6, 6, Some(RED), Some(RED), None);
+    assert_eq!(layer.get_cell(2, 2), con.get_cell(2, 2));
+    assert_eq!(layer.console.get_back(2, 2), Some((0, 0, 0, 0)));
+    assert_eq!(layer.console.get_back(0, 2), Some(RED));
+}
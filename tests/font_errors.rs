@@ -0,0 +1,43 @@
+use doryen_rs::{AppOptions, DoryenApi, Engine, FontLayout, HeadlessApp, UpdateEvent};
+
+/// adds a font that doesn't exist at the first update
+struct AddMissingFont;
+
+impl Engine for AddMissingFont {
+    fn init(&mut self, api: &mut dyn DoryenApi) {
+        assert_eq!(
+            api.add_font("missing_extra_font_8x8.png", FontLayout::Cp437),
+            1
+        );
+    }
+    fn update(&mut self, _api: &mut dyn DoryenApi) -> Option<UpdateEvent> {
+        None
+    }
+    fn render(&mut self, _api: &mut dyn DoryenApi) {}
+}
+
+fn app(font_path: &str) -> HeadlessApp {
+    let mut app = HeadlessApp::new(AppOptions {
+        font_path: font_path.to_owned(),
+        ..Default::default()
+    });
+    app.set_engine(Box::new(AddMissingFont));
+    app
+}
+
+#[test]
+fn extra_font_error_doesnt_replace_main_font_error() {
+    let mut app = app("missing_main_font_8x8.png");
+    app.run_ticks(3);
+    assert!(app.api().font_error(0).is_some());
+    assert!(app.api().font_error(1).is_some());
+    assert!(app.api().font_error(2).is_none());
+}
+
+#[test]
+fn extra_font_error_isnt_reported_as_main_font_error() {
+    let mut app = app("www/terminal_8x8.png");
+    app.run_ticks(3);
+    assert!(app.api().font_error(0).is_none());
+    assert!(app.api().font_error(1).is_some());
+}
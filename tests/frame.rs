@@ -0,0 +1,34 @@
+use doryen_rs::{Console, FrameStyle, CHAR_LINE_H, CHAR_LINE_V};
+
+fn glyphs(con: &Console, cells: &[(i32, i32)]) -> Vec<u32> {
+    cells
+        .iter()
+        .map(|&(x, y)| con.get_glyph(x, y).unwrap())
+        .collect()
+}
+
+#[test]
+fn single_column_frame_is_a_plain_line() {
+    let mut con = Console::new(5, 5);
+    con.frame(1, 1, 1, 3, &FrameStyle::SINGLE, None, None, None);
+    let line = u32::from(CHAR_LINE_V);
+    assert_eq!(glyphs(&con, &[(1, 1), (1, 2), (1, 3)]), vec![line; 3]);
+}
+
+#[test]
+fn single_row_frame_is_a_plain_line() {
+    let mut con = Console::new(5, 5);
+    con.frame(1, 1, 3, 1, &FrameStyle::SINGLE, None, None, None);
+    let line = u32::from(CHAR_LINE_H);
+    assert_eq!(glyphs(&con, &[(1, 1), (2, 1), (3, 1)]), vec![line; 3]);
+}
+
+#[test]
+fn single_row_frame_joins_other_frames() {
+    let mut con = Console::new(10, 5);
+    con.frame(0, 0, 10, 5, &FrameStyle::SINGLE, None, None, None);
+    // horizontal line crossing the box borders
+    con.frame(0, 2, 10, 1, &FrameStyle::SINGLE, None, None, None);
+    // ┼ where it crosses the borders, ─ inside the box
+    assert_eq!(glyphs(&con, &[(0, 2), (5, 2), (9, 2)]), vec![197, 196, 197]);
+}
@@ -0,0 +1,56 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use doryen_rs::{
+    AppEvent, AppOptions, DoryenApi, Engine, HeadlessApp, KeyDownEvent, ScanCode, UpdateEvent,
+};
+
+/// records the ticks where the space key was pressed
+struct KeyRecorder {
+    updates: Rc<RefCell<Vec<bool>>>,
+}
+
+impl Engine for KeyRecorder {
+    fn update(&mut self, api: &mut dyn DoryenApi) -> Option<UpdateEvent> {
+        let pressed = api.input().key_pressed(ScanCode::Space);
+        self.updates.borrow_mut().push(pressed);
+        None
+    }
+    fn render(&mut self, _api: &mut dyn DoryenApi) {}
+}
+
+fn space() -> AppEvent {
+    AppEvent::KeyDown(KeyDownEvent {
+        code: ScanCode::Space,
+        key: "Space".to_owned(),
+        shift: false,
+        alt: false,
+        ctrl: false,
+    })
+}
+
+fn recorder_app() -> (HeadlessApp, Rc<RefCell<Vec<bool>>>) {
+    let updates = Rc::new(RefCell::new(Vec::new()));
+    let mut app = HeadlessApp::new(AppOptions::default());
+    app.set_engine(Box::new(KeyRecorder {
+        updates: updates.clone(),
+    }));
+    (app, updates)
+}
+
+#[test]
+fn ticks_count_the_updates_after_a_long_step() {
+    let (mut app, updates) = recorder_app();
+    app.step(1.0);
+    assert_eq!(app.ticks(), updates.borrow().len() as u64);
+    app.step(1.0);
+    assert_eq!(app.ticks(), updates.borrow().len() as u64);
+}
+
+#[test]
+fn scheduled_event_arrives_at_its_tick_inside_a_step() {
+    let (mut app, updates) = recorder_app();
+    app.schedule_event(3, space());
+    app.step(5.0 / 60.0);
+    assert_eq!(*updates.borrow(), vec![false, false, false, true, false]);
+}
@@ -0,0 +1,159 @@
+use std::cell::RefCell;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use doryen_rs::{AppOptions, Console, DoryenApi, Engine, HeadlessApp, Image, UpdateEvent, XpFile};
+
+/// the watched files are shared by all the apps : run the tests one at a time
+static SERIAL: Mutex<()> = Mutex::new(());
+
+struct Idle;
+
+impl Engine for Idle {
+    fn update(&mut self, _api: &mut dyn DoryenApi) -> Option<UpdateEvent> {
+        None
+    }
+    fn render(&mut self, _api: &mut dyn DoryenApi) {}
+}
+
+/// write a file with a modification time different from the previous one
+fn write(path: &Path, content: &[u8], age: u64) {
+    fs::write(path, content).unwrap();
+    let mtime = SystemTime::now() - Duration::from_secs(age);
+    fs::File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_modified(mtime)
+        .unwrap();
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir =
+        std::env::temp_dir().join(format!("doryen_hot_reload_{}_{}", name, std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn hot_reload_app(engine: Box<dyn Engine>) -> HeadlessApp {
+    let mut app = HeadlessApp::new(AppOptions {
+        hot_reload: true,
+        ..Default::default()
+    });
+    app.set_engine(engine);
+    app
+}
+
+fn png(color: [u8; 4]) -> Vec<u8> {
+    let img = image::RgbaImage::from_pixel(2, 2, image::Rgba(color));
+    let mut buf = Vec::new();
+    image::DynamicImage::ImageRgba8(img)
+        .write_to(&mut buf, image::ImageOutputFormat::Png)
+        .unwrap();
+    buf
+}
+
+fn xp(glyph: char) -> Vec<u8> {
+    let mut con = Console::new(2, 2);
+    con.ascii(0, 0, glyph as u16);
+    con.to_xp()
+}
+
+#[test]
+fn modified_files_are_reloaded() {
+    let _serial = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+    let dir = temp_dir("reload");
+    let png_path = dir.join("image.png");
+    let xp_path = dir.join("map.xp");
+    write(&png_path, &png([255, 0, 0, 255]), 100);
+    write(&xp_path, &xp('@'), 100);
+
+    let mut app = hot_reload_app(Box::new(Idle));
+    let mut image = Image::new(png_path.to_str().unwrap());
+    let mut map = XpFile::new(xp_path.to_str().unwrap());
+    assert!(image.try_load());
+    assert_eq!(image.pixel(0, 0), Some((255, 0, 0, 255)));
+    assert_eq!(map.layers().unwrap()[0].get_ascii(0, 0), Some('@' as u16));
+
+    for (step, (color, glyph)) in [([0, 0, 255, 255], '#'), ([0, 255, 0, 255], '%')]
+        .into_iter()
+        .enumerate()
+    {
+        write(&png_path, &png(color), 50 - step as u64 * 10);
+        write(&xp_path, &xp(glyph), 50 - step as u64 * 10);
+        // let the engine poll the modification times
+        app.step(1.0);
+        assert!(image.try_load());
+        let (r, g, b, a) = (color[0], color[1], color[2], color[3]);
+        assert_eq!(image.pixel(0, 0), Some((r, g, b, a)));
+        assert_eq!(map.layers().unwrap()[0].get_ascii(0, 0), Some(glyph as u16));
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn files_loaded_without_hot_reload_are_not_watched() {
+    let _serial = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+    let dir = temp_dir("opt_in");
+    let png_path = dir.join("image.png");
+    write(&png_path, &png([255, 0, 0, 255]), 100);
+    let mut image = Image::new(png_path.to_str().unwrap());
+    assert!(image.try_load());
+
+    let mut app = hot_reload_app(Box::new(Idle));
+    write(&png_path, &png([0, 0, 255, 255]), 50);
+    app.step(1.0);
+    assert!(image.try_load());
+    assert_eq!(image.pixel(0, 0), Some((255, 0, 0, 255)));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/// watches a file and records the changes it's notified of
+struct ChangeRecorder {
+    path: String,
+    changes: Rc<RefCell<Vec<String>>>,
+}
+
+impl Engine for ChangeRecorder {
+    fn init(&mut self, api: &mut dyn DoryenApi) {
+        api.watch_file(&self.path);
+    }
+    fn update(&mut self, _api: &mut dyn DoryenApi) -> Option<UpdateEvent> {
+        None
+    }
+    fn render(&mut self, _api: &mut dyn DoryenApi) {}
+    fn file_changed(&mut self, _api: &mut dyn DoryenApi, path: &str) {
+        self.changes.borrow_mut().push(path.to_owned());
+    }
+}
+
+#[test]
+fn every_app_is_notified_with_its_own_clock() {
+    let _serial = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+    let dir = temp_dir("clocks");
+    let path = dir.join("data.txt");
+    write(&path, b"1", 100);
+    let path = path.to_str().unwrap().to_owned();
+    let recorder_app = || {
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        let app = hot_reload_app(Box::new(ChangeRecorder {
+            path: path.clone(),
+            changes: changes.clone(),
+        }));
+        (app, changes)
+    };
+    let (mut late, late_changes) = recorder_app();
+    let (mut early, early_changes) = recorder_app();
+    late.step(100.0);
+    early.step(1.0);
+
+    write(Path::new(&path), b"2", 50);
+    late.step(1.0);
+    early.step(1.0);
+    assert_eq!(*late_changes.borrow(), vec![path.clone()]);
+    assert_eq!(*early_changes.borrow(), vec![path.clone()]);
+    fs::remove_dir_all(&dir).unwrap();
+}
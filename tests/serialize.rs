@@ -0,0 +1,51 @@
+use doryen_rs::{Console, CONSOLE_FORMAT_VERSION};
+
+/// console data with the given size, no color and no cell
+fn header(width: u32, height: u32) -> Vec<u8> {
+    let mut data = b"DRYC".to_vec();
+    data.extend_from_slice(&CONSOLE_FORMAT_VERSION.to_le_bytes());
+    data.extend_from_slice(&width.to_le_bytes());
+    data.extend_from_slice(&height.to_le_bytes());
+    data.extend_from_slice(&0u32.to_le_bytes());
+    data
+}
+
+#[test]
+fn size_larger_than_the_data_is_an_error() {
+    assert!(Console::from_bytes(&header(u32::MAX, u32::MAX)).is_err());
+    assert!(Console::from_bytes(&header(u32::MAX, 1)).is_err());
+    assert!(Console::from_bytes(&header(100_000, 100_000)).is_err());
+    assert!(Console::from_bytes(&header(2, 2)).is_err());
+}
+
+#[test]
+fn truncated_data_is_an_error() {
+    let mut data = Console::new(5, 3).to_bytes();
+    data.pop();
+    assert!(Console::from_bytes(&data).is_err());
+}
+
+#[test]
+fn empty_console_roundtrip() {
+    let con = Console::from_bytes(&header(0, 0)).unwrap();
+    assert_eq!(con.get_size(), (0, 0));
+}
+
+#[test]
+fn zero_height_with_huge_width_is_an_error() {
+    assert!(Console::from_bytes(&header(1 << 29, 0)).is_err());
+    assert!(Console::from_bytes(&header(0, 1 << 29)).is_err());
+    assert!(Console::from_bytes(&header(1, 0)).is_err());
+}
+
+#[test]
+fn width_too_large_for_the_padding_is_an_error() {
+    assert!(Console::from_bytes(&header((1 << 31) + 1, 0)).is_err());
+    assert!(Console::from_bytes(&header((1 << 31) + 1, 1)).is_err());
+}
+
+#[test]
+fn padded_size_is_bounded() {
+    assert!(Console::from_bytes(&header(1 << 20, 1 << 20)).is_err());
+    assert!(Console::from_bytes(&header(u32::MAX, u32::MAX)).is_err());
+}
@@ -0,0 +1,77 @@
+use doryen_rs::Console;
+
+const MARK: Option<u16> = Some('#' as u16);
+
+/// draw a shape on a small console and on a large one where it's not clipped,
+/// and check that the small console is a window of the large one
+fn assert_clipped_like_unclipped(draw: impl Fn(&mut Console, i32, i32)) {
+    const OFFSET: i32 = 100;
+    let mut small = Console::new(20, 10);
+    let mut large = Console::new(256, 256);
+    draw(&mut small, 0, 0);
+    draw(&mut large, OFFSET, OFFSET);
+    for y in 0..10 {
+        for x in 0..20 {
+            assert_eq!(
+                small.get_ascii(x, y),
+                large.get_ascii(x + OFFSET, y + OFFSET),
+                "cell {} {}",
+                x,
+                y
+            );
+        }
+    }
+}
+
+#[test]
+fn lines_crossing_the_border_are_clipped() {
+    for &(x1, y1, x2, y2) in &[
+        (-50, -30, 70, 40),
+        (70, 40, -50, -30),
+        (-5, 80, 15, -60),
+        (25, 3, -40, 7),
+        (3, -20, 9, 25),
+    ] {
+        assert_clipped_like_unclipped(|con, ox, oy| {
+            con.line(x1 + ox, y1 + oy, x2 + ox, y2 + oy, MARK, None, None)
+        });
+    }
+}
+
+#[test]
+fn ellipses_crossing_the_border_are_clipped() {
+    assert_clipped_like_unclipped(|con, ox, oy| con.circle(10 + ox, 5 + oy, 30, MARK, None, None));
+    assert_clipped_like_unclipped(|con, ox, oy| {
+        con.ellipse(-3 + ox, 12 + oy, 40, 9, MARK, None, None)
+    });
+    assert_clipped_like_unclipped(|con, ox, oy| {
+        con.fill_ellipse(25 + ox, -2 + oy, 12, 7, MARK, None, None)
+    });
+}
+
+#[test]
+fn far_shapes_are_clipped_before_iterating() {
+    let mut con = Console::new(10, 10);
+    con.line(0, 0, 2_000_000_000, 5, MARK, None, None);
+    assert_eq!(con.get_ascii(0, 0), MARK);
+    con.line(i32::MIN, i32::MIN, i32::MAX, i32::MAX, MARK, None, None);
+    assert_eq!(con.get_ascii(9, 9), MARK);
+    con.fill_circle(5, 5, 400_000_000, MARK, None, None);
+    assert_eq!(con.get_ascii(9, 0), MARK);
+
+    let mut con = Console::new(10, 10);
+    con.circle(5, 5, 400_000_000, MARK, None, None);
+    assert_eq!(con.get_ascii(5, 5), Some(' ' as u16));
+    con.ellipse(i32::MAX, i32::MIN, u32::MAX, u32::MAX, MARK, None, None);
+    con.fill_polygon(
+        &[
+            (i32::MIN, i32::MIN),
+            (i32::MAX, i32::MIN),
+            (i32::MIN, i32::MAX),
+        ],
+        MARK,
+        None,
+        None,
+    );
+    assert_eq!(con.get_ascii(0, 0), MARK);
+}
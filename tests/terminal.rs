@@ -0,0 +1,75 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use doryen_rs::{AppOptions, DoryenApi, Engine, ScanCode, TerminalApp, UpdateEvent};
+
+#[derive(Default)]
+struct Received {
+    escape: bool,
+    text: String,
+    mouse: (f32, f32),
+}
+
+/// records the input received by the updates
+struct InputRecorder(Rc<RefCell<Received>>);
+
+impl Engine for InputRecorder {
+    fn update(&mut self, api: &mut dyn DoryenApi) -> Option<UpdateEvent> {
+        let mut received = self.0.borrow_mut();
+        received.escape |= api.input().key_pressed(ScanCode::Escape);
+        received.text.push_str(&api.input().text());
+        received.mouse = api.input().mouse_pos();
+        None
+    }
+    fn render(&mut self, _api: &mut dyn DoryenApi) {}
+}
+
+/// a terminal app recording the input received by its updates
+fn recorder_app() -> (TerminalApp<Vec<u8>>, Rc<RefCell<Received>>) {
+    let received = Rc::new(RefCell::new(Received::default()));
+    let mut app = TerminalApp::new(
+        AppOptions {
+            console_width: 10,
+            console_height: 5,
+            ..Default::default()
+        },
+        Vec::new(),
+    );
+    app.set_engine(Box::new(InputRecorder(received.clone())));
+    (app, received)
+}
+
+#[test]
+fn escape_sequence_split_across_reads() {
+    let (mut app, received) = recorder_app();
+    app.feed_input(b"\x1b");
+    app.feed_input(b"[<35;5");
+    app.step(1.0 / 60.0).unwrap();
+    app.feed_input(b";3M");
+    app.step(2.0 / 60.0).unwrap();
+    app.step(3.0 / 60.0).unwrap();
+    let received = received.borrow();
+    assert!(!received.escape);
+    assert_eq!(received.mouse, (4.0, 2.0));
+    assert_eq!(received.text, "");
+}
+
+#[test]
+fn utf8_character_split_across_reads() {
+    let (mut app, received) = recorder_app();
+    app.feed_input("a\u{e9}".as_bytes().split_last().unwrap().1);
+    app.step(1.0 / 60.0).unwrap();
+    app.feed_input(&"\u{e9}b".as_bytes()[1..]);
+    app.step(2.0 / 60.0).unwrap();
+    assert_eq!(received.borrow().text, "a\u{e9}b");
+}
+
+#[test]
+fn lone_escape_is_a_key_press() {
+    let (mut app, received) = recorder_app();
+    app.feed_input(b"\x1b");
+    app.step(1.0 / 60.0).unwrap();
+    app.step(2.0 / 60.0).unwrap();
+    app.step(3.0 / 60.0).unwrap();
+    assert!(received.borrow().escape);
+}
@@ -0,0 +1,54 @@
+use std::io::Write;
+
+use doryen_rs::Bundle;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+
+/// a zip archive containing a single deflated file, with the given uncompressed size in the headers
+fn zip(name: &str, content: &[u8], size: u32) -> Vec<u8> {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(content).unwrap();
+    let compressed = encoder.finish().unwrap();
+    let mut data = Vec::new();
+    // local file header
+    data.extend_from_slice(b"PK\x03\x04");
+    data.extend_from_slice(&[20, 0, 0, 0, 8, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    data.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+    data.extend_from_slice(&size.to_le_bytes());
+    data.extend_from_slice(&(name.len() as u16).to_le_bytes());
+    data.extend_from_slice(&0u16.to_le_bytes());
+    data.extend_from_slice(name.as_bytes());
+    data.extend_from_slice(&compressed);
+    // central directory
+    let directory = data.len() as u32;
+    data.extend_from_slice(b"PK\x01\x02");
+    data.extend_from_slice(&[20, 0, 20, 0, 0, 0, 8, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    data.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+    data.extend_from_slice(&size.to_le_bytes());
+    data.extend_from_slice(&(name.len() as u16).to_le_bytes());
+    data.extend_from_slice(&[0; 12]);
+    data.extend_from_slice(&0u32.to_le_bytes());
+    data.extend_from_slice(name.as_bytes());
+    let directory_size = data.len() as u32 - directory;
+    // end of central directory
+    data.extend_from_slice(b"PK\x05\x06");
+    data.extend_from_slice(&[0, 0, 0, 0, 1, 0, 1, 0]);
+    data.extend_from_slice(&directory_size.to_le_bytes());
+    data.extend_from_slice(&directory.to_le_bytes());
+    data.extend_from_slice(&0u16.to_le_bytes());
+    data
+}
+
+#[test]
+fn deflated_zip_entry() {
+    let content = b"hello hello hello hello";
+    let bundle = Bundle::from_archive(&zip("a.txt", content, content.len() as u32)).unwrap();
+    assert_eq!(bundle.get("a.txt"), Some(&content[..]));
+}
+
+#[test]
+fn zip_entry_with_a_wrong_size_is_an_error() {
+    let content = b"hello hello hello hello";
+    assert!(Bundle::from_archive(&zip("a.txt", content, u32::MAX)).is_err());
+    assert!(Bundle::from_archive(&zip("a.txt", content, 4)).is_err());
+}
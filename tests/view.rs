@@ -0,0 +1,18 @@
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use doryen_rs::Console;
+
+#[test]
+fn console_is_restored_when_view_drawing_panics() {
+    let mut con = Console::new(20, 10);
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let mut view = con.view_mut(5, 5, 4, 3);
+        view.flood_fill(0, 0, |_, _, _| panic!("predicate"), None, None, None);
+    }));
+    assert!(result.is_err());
+    assert_eq!(con.get_size(), (20, 10));
+    con.ascii(0, 0, '@' as u16);
+    assert_eq!(con.get_ascii(0, 0), Some('@' as u16));
+    con.ascii(19, 9, '#' as u16);
+    assert_eq!(con.get_ascii(19, 9), Some('#' as u16));
+}
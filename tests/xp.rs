@@ -0,0 +1,50 @@
+use std::io::{Read, Write};
+
+use doryen_rs::Console;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+fn compress(data: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+/// a xp file with a single layer of the given size but no cell data
+fn xp_header(width: i32, height: i32) -> Vec<u8> {
+    let mut data = Vec::new();
+    for value in [-1, 1, width, height] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    compress(&data)
+}
+
+#[test]
+fn negative_layer_size_is_an_error() {
+    assert!(Console::from_xp(&xp_header(-1, 10)).is_err());
+    assert!(Console::from_xp(&xp_header(10, i32::MIN)).is_err());
+    assert!(Console::from_xp(&xp_header(0, 10)).is_err());
+}
+
+#[test]
+fn layer_size_larger_than_the_file_is_an_error() {
+    assert!(Console::from_xp(&xp_header(i32::MAX, i32::MAX)).is_err());
+    assert!(Console::from_xp(&xp_header(100_000, 100_000)).is_err());
+    assert!(Console::from_xp(&xp_header(2, 2)).is_err());
+}
+
+#[test]
+fn truncated_file_is_an_error() {
+    let xp = Console::new(4, 3).to_xp();
+    let mut data = Vec::new();
+    flate2::read::GzDecoder::new(&xp[..])
+        .read_to_end(&mut data)
+        .unwrap();
+    data.truncate(data.len() - 1);
+    assert!(Console::from_xp(&compress(&data)).is_err());
+    data.push(0);
+    assert_eq!(
+        Console::from_xp(&compress(&data)).unwrap()[0].get_size(),
+        (4, 3)
+    );
+}